@@ -0,0 +1,12 @@
+-- 修改未读数量，不会小于 0
+-- 缓存不存在时不处理，获取未读数量时会从数据库重新统计
+-- KEYS[1] 未读数量 ARGV[1] 变化的数量
+if redis.call('exists', KEYS[1]) == 0 then
+    return nil
+end
+local count = redis.call('incrby', KEYS[1], ARGV[1])
+if count < 0 then
+    redis.call('set', KEYS[1], 0)
+    return 0
+end
+return count
@@ -0,0 +1,8 @@
+-- 写入重放缓存并发布给所有实例
+-- KEYS[1] 重放缓存 STREAM
+-- ARGV[1] 缓存最大长度 ARGV[2] 缓存过期秒数 ARGV[3] 频道 ARGV[4] 用户id ARGV[5] 事件
+redis.replicate_commands()
+local id = redis.call('xadd', KEYS[1], 'MAXLEN', '~', ARGV[1], '*', 'data', ARGV[5])
+redis.call('expire', KEYS[1], ARGV[2])
+redis.call('publish', ARGV[3], ARGV[4] .. ' ' .. id .. ' ' .. ARGV[5])
+return id
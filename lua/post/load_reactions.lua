@@ -0,0 +1,12 @@
+-- 从 postgres 的数据重建点赞/反感缓存
+-- KEYS[1] 集合 KEYS[2] 数量
+-- ARGV 集合成员
+-- 数量已存在说明缓存已经建立，不覆盖
+if redis.call('exists', KEYS[2]) == 1 then
+    return 0
+end
+for i = 1, #ARGV do
+    redis.call('sadd', KEYS[1], ARGV[i])
+end
+redis.call('set', KEYS[2], redis.call('scard', KEYS[1]))
+return 1
@@ -0,0 +1,29 @@
+-- 原子地点赞/反感，点赞会取消反感，反之亦然
+-- KEYS[1] 集合 KEYS[2] 数量 KEYS[3] 相反的集合 KEYS[4] 相反的数量 KEYS[5] 变更列表
+-- ARGV[1] 用户id ARGV[2] 1 添加 0 取消 ARGV[3] 本次的变更 ARGV[4] 取消相反表态的变更
+-- 返回 {是否改变, 数量, 相反的数量, 是否在集合, 是否在相反的集合}
+local changed = 0
+if ARGV[2] == '1' then
+    if redis.call('sadd', KEYS[1], ARGV[1]) == 1 then
+        changed = 1
+        redis.call('incr', KEYS[2])
+        redis.call('rpush', KEYS[5], ARGV[3])
+        if redis.call('srem', KEYS[3], ARGV[1]) == 1 then
+            redis.call('decr', KEYS[4])
+            redis.call('rpush', KEYS[5], ARGV[4])
+        end
+    end
+else
+    if redis.call('srem', KEYS[1], ARGV[1]) == 1 then
+        changed = 1
+        redis.call('decr', KEYS[2])
+        redis.call('rpush', KEYS[5], ARGV[3])
+    end
+end
+return {
+    changed,
+    tonumber(redis.call('get', KEYS[2]) or 0),
+    tonumber(redis.call('get', KEYS[4]) or 0),
+    redis.call('sismember', KEYS[1], ARGV[1]),
+    redis.call('sismember', KEYS[3], ARGV[1]),
+}
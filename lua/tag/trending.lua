@@ -0,0 +1,7 @@
+-- 合并最近几个小时的标签使用次数，结果缓存一段时间
+-- KEYS[1] 合并结果 KEYS[2..] 每小时的使用次数 ARGV[1] 缓存秒数 ARGV[2] 数量
+if redis.call('exists', KEYS[1]) == 0 then
+    redis.call('zunionstore', KEYS[1], #KEYS - 1, unpack(KEYS, 2))
+    redis.call('expire', KEYS[1], ARGV[1])
+end
+return redis.call('zrevrange', KEYS[1], 0, ARGV[2] - 1, 'withscores')
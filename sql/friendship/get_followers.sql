@@ -0,0 +1,4 @@
+SELECT requester_id FROM main.friendship
+WHERE addressee_id = $1 AND ($2::integer IS NULL OR requester_id > $2)
+ORDER BY requester_id
+LIMIT $3;
@@ -0,0 +1,28 @@
+-- 点赞/反感落库
+-- 表建好后执行 wepo migrate-reactions，把 redis 上已有的点赞/反感导入
+
+-- 点赞表 post删除时自动删除
+CREATE TABLE IF NOT EXISTS main.post_likes
+(
+    -- post id
+    post_id bigint NOT NULL REFERENCES main.posts(id) ON DELETE CASCADE,
+    -- 点赞的用户
+    user_id integer NOT NULL REFERENCES main.users(id) ON DELETE CASCADE,
+    -- 创建时间
+    create_time timestamp without time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- 主键约束
+    CONSTRAINT post_likes_pkey PRIMARY KEY (post_id, user_id)
+);
+
+-- 反感表 post删除时自动删除
+CREATE TABLE IF NOT EXISTS main.post_hates
+(
+    -- post id
+    post_id bigint NOT NULL REFERENCES main.posts(id) ON DELETE CASCADE,
+    -- 反感的用户
+    user_id integer NOT NULL REFERENCES main.users(id) ON DELETE CASCADE,
+    -- 创建时间
+    create_time timestamp without time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- 主键约束
+    CONSTRAINT post_hates_pkey PRIMARY KEY (post_id, user_id)
+);
@@ -0,0 +1,15 @@
+-- 首页时间线
+ALTER TABLE main.users ADD COLUMN IF NOT EXISTS followers integer NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS friendship_addressee_idx ON main.friendship (addressee_id);
+
+-- 关注者数量，之后由 trigger.sql 里的触发器维护
+UPDATE main.users AS u
+SET followers = f.total
+FROM (
+    SELECT u1.id, count(f1.requester_id)::integer AS total
+    FROM main.users AS u1
+    LEFT JOIN main.friendship AS f1 ON f1.addressee_id = u1.id
+    GROUP BY u1.id
+) AS f
+WHERE u.id = f.id AND u.followers <> f.total;
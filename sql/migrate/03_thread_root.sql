@@ -0,0 +1,20 @@
+-- 多层回复
+ALTER TABLE main.posts ADD COLUMN IF NOT EXISTS root_id bigint;
+
+CREATE INDEX IF NOT EXISTS posts_root_idx ON main.posts (root_id);
+
+-- 对话的根 post：沿着 extends 往上找，原文已经不存在的评论自己作为根
+WITH RECURSIVE tree AS (
+    SELECT p.id, p.id AS root_id
+    FROM main.posts AS p
+    WHERE p.extends IS NULL
+        OR NOT EXISTS (SELECT 1 FROM main.posts AS o WHERE o.id = p.extends)
+    UNION ALL
+    SELECT p.id, tree.root_id
+    FROM main.posts AS p, tree
+    WHERE p.extends = tree.id
+)
+UPDATE main.posts AS p
+SET root_id = tree.root_id
+FROM tree
+WHERE p.id = tree.id AND p.root_id IS DISTINCT FROM tree.root_id;
@@ -0,0 +1,21 @@
+-- 编辑 post
+ALTER TABLE main.posts
+    ADD COLUMN IF NOT EXISTS edited_at timestamp without time zone,
+    ADD COLUMN IF NOT EXISTS revisions integer NOT NULL DEFAULT 0;
+
+-- post 的历史版本 post删除时自动删除
+CREATE TABLE IF NOT EXISTS main.post_revisions
+(
+    -- id
+    id bigserial NOT NULL,
+    -- post id
+    post_id bigint NOT NULL REFERENCES main.posts(id) ON DELETE CASCADE,
+    -- 编辑前的内容
+    content text NOT NULL,
+    -- 被替换的时间
+    create_time timestamp without time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- 主键约束
+    CONSTRAINT post_revisions_pkey PRIMARY KEY (id)
+);
+
+CREATE INDEX IF NOT EXISTS post_revisions_post_idx ON main.post_revisions (post_id);
@@ -0,0 +1,4 @@
+-- 删除 post 时只清空内容，保留回复
+ALTER TABLE main.posts ADD COLUMN IF NOT EXISTS deleted_at timestamp without time zone;
+
+CREATE INDEX IF NOT EXISTS posts_extends_idx ON main.posts (extends);
@@ -0,0 +1,6 @@
+-- 通知的已读状态
+-- 已有的通知当作已读，之后的通知默认未读
+ALTER TABLE main.notices ADD COLUMN IF NOT EXISTS read boolean NOT NULL DEFAULT TRUE;
+ALTER TABLE main.notices ALTER COLUMN read SET DEFAULT FALSE;
+
+CREATE INDEX IF NOT EXISTS notices_unread_idx ON main.notices (addressee_id, notice_type) WHERE NOT read;
@@ -0,0 +1,40 @@
+-- 通知设置和屏蔽
+
+-- 关闭的通知类型
+CREATE TABLE IF NOT EXISTS main.notice_preferences
+(
+    -- 用户
+    user_id integer NOT NULL REFERENCES main.users(id) ON DELETE CASCADE,
+    -- 通知类型
+    notice_type smallint NOT NULL,
+    -- 是否接收，没有记录时接收
+    enabled boolean NOT NULL DEFAULT TRUE,
+    -- 主键约束
+    CONSTRAINT notice_preferences_pkey PRIMARY KEY (user_id, notice_type)
+);
+
+-- 屏蔽的用户，不接收这个用户的通知
+CREATE TABLE IF NOT EXISTS main.notice_muted_users
+(
+    -- 用户
+    user_id integer NOT NULL REFERENCES main.users(id) ON DELETE CASCADE,
+    -- 被屏蔽的用户
+    muted_id integer NOT NULL REFERENCES main.users(id) ON DELETE CASCADE,
+    -- 创建时间
+    create_time timestamp without time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- 主键约束
+    CONSTRAINT notice_muted_users_pkey PRIMARY KEY (user_id, muted_id)
+);
+
+-- 屏蔽的 post，不接收这个 post 和它的回复的通知
+CREATE TABLE IF NOT EXISTS main.notice_muted_posts
+(
+    -- 用户
+    user_id integer NOT NULL REFERENCES main.users(id) ON DELETE CASCADE,
+    -- 被屏蔽的 post
+    post_id bigint NOT NULL REFERENCES main.posts(id) ON DELETE CASCADE,
+    -- 创建时间
+    create_time timestamp without time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- 主键约束
+    CONSTRAINT notice_muted_posts_pkey PRIMARY KEY (user_id, post_id)
+);
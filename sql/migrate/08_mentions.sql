@@ -0,0 +1,2 @@
+-- @ 提到的用户
+ALTER TABLE main.posts ADD COLUMN IF NOT EXISTS mentions jsonb NOT NULL DEFAULT '[]';
@@ -0,0 +1,16 @@
+-- 话题标签
+
+-- post 的话题标签
+CREATE TABLE IF NOT EXISTS main.post_tags
+(
+    -- post
+    post_id bigint NOT NULL REFERENCES main.posts(id) ON DELETE CASCADE,
+    -- 标签（小写，不包括 #）
+    tag character varying(32) NOT NULL,
+    -- 创建时间
+    create_time timestamp without time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- 主键约束
+    CONSTRAINT post_tags_pkey PRIMARY KEY (post_id, tag)
+);
+
+CREATE INDEX IF NOT EXISTS post_tags_tag_idx ON main.post_tags (tag, post_id DESC);
@@ -0,0 +1,6 @@
+-- 全文搜索
+
+ALTER TABLE main.posts
+    ADD COLUMN IF NOT EXISTS search_vector tsvector GENERATED ALWAYS AS (to_tsvector('simple', content)) STORED;
+
+CREATE INDEX IF NOT EXISTS posts_search_idx ON main.posts USING GIN (search_vector);
@@ -0,0 +1,7 @@
+-- 模糊搜索用户
+
+CREATE EXTENSION IF NOT EXISTS "pg_trgm"
+    WITH SCHEMA main CASCADE;
+
+CREATE INDEX IF NOT EXISTS users_nick_trgm_idx ON main.users USING GIN (nick main.gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS users_nick_lower_idx ON main.users (lower(nick) text_pattern_ops);
@@ -0,0 +1,6 @@
+-- 用户主页
+
+ALTER TABLE main.users
+    ADD COLUMN IF NOT EXISTS bio character varying(160) NOT NULL DEFAULT '';
+
+CREATE INDEX IF NOT EXISTS posts_sender_idx ON main.posts (sender, id DESC);
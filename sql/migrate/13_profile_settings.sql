@@ -0,0 +1,8 @@
+-- 个人资料设置
+
+ALTER TABLE main.users
+    ADD COLUMN IF NOT EXISTS display_name character varying(30) NOT NULL DEFAULT '',
+    ADD COLUMN IF NOT EXISTS location character varying(30) NOT NULL DEFAULT '',
+    ADD COLUMN IF NOT EXISTS website character varying(100) NOT NULL DEFAULT '',
+    ADD COLUMN IF NOT EXISTS birthday DATE,
+    ADD COLUMN IF NOT EXISTS updated_at timestamp without time zone;
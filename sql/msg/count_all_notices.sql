@@ -0,0 +1,3 @@
+SELECT count(*) AS total
+FROM main.notices
+WHERE addressee_id = $1 AND notice_type = ANY($2);
@@ -0,0 +1,4 @@
+SELECT count(*) AS total
+FROM main.notices
+WHERE notice_type = $1 and addressee_id = $2 and sender_object = $3
+    and create_time >= $4 and create_time < $4 + $5::bigint * interval '1 second';
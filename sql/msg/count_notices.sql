@@ -0,0 +1,3 @@
+SELECT count(*) AS total
+FROM main.notices
+WHERE notice_type = $1 and addressee_id = $2;
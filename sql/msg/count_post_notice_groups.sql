@@ -0,0 +1,3 @@
+SELECT count(DISTINCT (sender_object, floor(extract(epoch FROM create_time) / $3::bigint))) AS total
+FROM main.notices
+WHERE notice_type = $1 AND addressee_id = $2;
@@ -0,0 +1,4 @@
+SELECT notice_type, count(*) AS total
+FROM main.notices
+WHERE addressee_id = $1 AND NOT read
+GROUP BY notice_type;
@@ -1,2 +1,9 @@
+-- 删除 post 的评论/点赞/反感/@ 通知，以及回复这个 post 的评论通知
 DELETE FROM main.notices
-WHERE addressee_id = $1 and notice_type in ($2, $3);
\ No newline at end of file
+WHERE notice_type IN ($3, $4, $5, $6) AND (
+    sender_object = $1
+    OR (notice_type = $3 AND sender_object IN (
+        SELECT id::text FROM main.posts WHERE extends = $2
+    ))
+)
+RETURNING addressee_id, notice_type, read;
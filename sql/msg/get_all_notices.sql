@@ -0,0 +1,25 @@
+-- 所有类型的通知，按时间合并
+-- $3 sender_object 为 post id 的类型 $4 需要返回原文的类型
+SELECT
+    n.id,
+    n.read,
+    n.notice_type,
+    n.create_time,
+    n.sender_object,
+    u.id AS sender_id,
+    u.nick AS sender_nick,
+    u.avatar_url AS sender_avatar_url,
+    p.content::varchar(50) AS content,
+    p.extends AS origin_id,
+    p.root_id,
+    p2.content::varchar(20) AS origin,
+    p2.create_time AS origin_create_time
+FROM main.notices as n
+LEFT JOIN main.users as u ON u.id = n.sender
+LEFT JOIN main.posts as p ON p.id = CASE
+    WHEN n.notice_type = ANY($3) THEN CAST(n.sender_object as bigint)
+END
+LEFT JOIN main.posts as p2 ON n.notice_type = ANY($4) AND p.extends = p2.id
+WHERE addressee_id = $1 AND notice_type = ANY($2)
+ORDER BY n.create_time DESC, n.id DESC
+LIMIT $5 OFFSET $6;
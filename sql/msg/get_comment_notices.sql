@@ -1,6 +1,6 @@
 SELECT
     n.id,
-    -- n.read,
+    n.read,
     n.create_time,
     n.sender_object,
     u.id AS sender_id,
@@ -8,6 +8,7 @@ SELECT
     u.avatar_url AS sender_avatar_url,
     p.content::varchar(50) AS content,
     p.extends AS origin_id,
+    p.root_id,
     p2.content::varchar(20) AS origin,
     p2.create_time AS origin_create_time
 FROM main.notices as n
@@ -0,0 +1,3 @@
+SELECT notice_type
+FROM main.notice_preferences
+WHERE user_id = $1 AND NOT enabled;
@@ -0,0 +1,18 @@
+-- 获取某一组聚合通知中的单条通知
+-- $3 post id $4 时间窗口开始 $5 时间窗口（秒）
+SELECT
+    n.id,
+    n.read,
+    n.create_time,
+    n.sender_object,
+    u.id AS sender_id,
+    u.nick AS sender_nick,
+    u.avatar_url AS sender_avatar_url,
+    p.content::varchar(15) AS content
+FROM main.notices as n
+LEFT JOIN main.users as u ON u.id = n.sender
+LEFT JOIN main.posts as p ON p.id = CAST(n.sender_object as bigint)
+WHERE notice_type = $1 and addressee_id = $2 and sender_object = $3
+    and n.create_time >= $4 and n.create_time < $4 + $5::bigint * interval '1 second'
+ORDER BY n.create_time DESC
+LIMIT $6 OFFSET $7;
@@ -0,0 +1,4 @@
+SELECT post_id
+FROM main.notice_muted_posts
+WHERE user_id = $1
+ORDER BY create_time DESC;
@@ -0,0 +1,5 @@
+SELECT u.id, u.nick, u.avatar_url
+FROM main.notice_muted_users AS m
+JOIN main.users AS u ON u.id = m.muted_id
+WHERE m.user_id = $1
+ORDER BY m.create_time DESC;
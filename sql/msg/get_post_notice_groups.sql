@@ -0,0 +1,36 @@
+-- 按 post 和时间窗口聚合点赞/反感通知
+-- $3 时间窗口（秒） $4 每组返回的发送者数量
+WITH grouped AS (
+    SELECT
+        n.sender_object,
+        floor(extract(epoch FROM n.create_time) / $3::bigint)::bigint AS bucket,
+        count(*) AS total,
+        count(*) FILTER (WHERE NOT n.read) AS unread,
+        max(n.create_time) AS create_time,
+        (array_agg(n.sender ORDER BY n.create_time DESC))[1:$4::int] AS sender_ids
+    FROM main.notices AS n
+    WHERE n.notice_type = $1 AND n.addressee_id = $2
+    GROUP BY n.sender_object, bucket
+)
+SELECT
+    g.sender_object,
+    g.total,
+    g.unread,
+    g.create_time,
+    to_timestamp(g.bucket * $3::bigint) AT TIME ZONE 'UTC' AS window_start,
+    s.sender_ids,
+    s.sender_nicks,
+    s.sender_avatar_urls,
+    p.content::varchar(15) AS content
+FROM grouped AS g
+LEFT JOIN LATERAL (
+    SELECT
+        array_agg(u.id ORDER BY ids.ord) AS sender_ids,
+        array_agg(u.nick ORDER BY ids.ord) AS sender_nicks,
+        array_agg(u.avatar_url ORDER BY ids.ord) AS sender_avatar_urls
+    FROM unnest(g.sender_ids) WITH ORDINALITY AS ids(id, ord)
+    JOIN main.users AS u ON u.id = ids.id
+) AS s ON true
+LEFT JOIN main.posts AS p ON p.id = CAST(g.sender_object AS bigint)
+ORDER BY g.create_time DESC
+LIMIT $5 OFFSET $6;
@@ -1,4 +1,26 @@
+-- 接收者关闭了这个类型、屏蔽了发送者或者屏蔽了相关的 post 时不插入
+-- $5 sender_object 为 post id 的类型
+WITH target AS (
+    SELECT CASE
+        WHEN $2::smallint = ANY($5::smallint[]) THEN CAST($3::text AS bigint)
+    END AS post_id
+)
 INSERT INTO main.notices
     (sender, notice_type, sender_object, addressee_id)
-VALUES ($1, $2, $3, $4)
-RETURNING *
\ No newline at end of file
+SELECT $1::integer, $2::smallint, $3::text, $4::integer
+FROM target AS t
+WHERE NOT EXISTS (
+    SELECT 1 FROM main.notice_preferences
+    WHERE user_id = $4 AND notice_type = $2 AND NOT enabled
+) AND NOT EXISTS (
+    SELECT 1 FROM main.notice_muted_users
+    WHERE user_id = $4 AND muted_id = $1
+) AND NOT EXISTS (
+    -- post 本身、回复的 post 和所在对话的根 post
+    SELECT 1
+    FROM main.notice_muted_posts AS m
+    LEFT JOIN main.posts AS p ON p.id = t.post_id
+    WHERE m.user_id = $4
+        AND m.post_id IN (t.post_id, p.extends, p.root_id)
+)
+RETURNING *
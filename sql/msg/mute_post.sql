@@ -0,0 +1,3 @@
+INSERT INTO main.notice_muted_posts (user_id, post_id)
+SELECT $1, id FROM main.posts WHERE id = $2
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,3 @@
+INSERT INTO main.notice_muted_users (user_id, muted_id)
+SELECT $1, id FROM main.users WHERE id = $2
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,3 @@
+INSERT INTO main.notice_preferences (user_id, notice_type, enabled)
+VALUES ($1, $2, $3)
+ON CONFLICT (user_id, notice_type) DO UPDATE SET enabled = EXCLUDED.enabled;
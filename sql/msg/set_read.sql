@@ -1 +1,3 @@
--- UPDATE main.notices SET read = true WHERE id in $1;
\ No newline at end of file
+UPDATE main.notices SET read = true
+WHERE addressee_id = $1 AND id = ANY($2) AND NOT read
+RETURNING notice_type;
@@ -0,0 +1,3 @@
+-- $2 为空时全部已读
+UPDATE main.notices SET read = true
+WHERE addressee_id = $1 AND NOT read AND ($2::smallint IS NULL OR notice_type = $2);
@@ -0,0 +1,2 @@
+DELETE FROM main.notice_muted_posts
+WHERE user_id = $1 AND post_id = $2;
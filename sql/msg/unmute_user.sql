@@ -0,0 +1,2 @@
+DELETE FROM main.notice_muted_users
+WHERE user_id = $1 AND muted_id = $2;
@@ -1,3 +1,3 @@
-INSERT INTO main.posts(id, sender, content)
-VALUES ($1, $2, $3)
-RETURNING id;
\ No newline at end of file
+INSERT INTO main.posts(id, sender, content, root_id, mentions)
+VALUES ($1, $2, $3, $1, $4)
+RETURNING id;
@@ -1,8 +1,10 @@
 WITH rows AS (
     INSERT INTO main.posts
-        (id, sender, content, extends)
-    VALUES
-        ($1, $2, $3, $4)
+        (id, sender, content, extends, root_id, mentions)
+    SELECT
+        $1, $2, $3, old.id, COALESCE(old.root_id, old.id), $5
+    FROM main.posts AS old
+    WHERE old.id = $4 AND old.deleted_at IS NULL
     RETURNING *
 )
 SELECT 
@@ -10,4 +12,4 @@ SELECT
     r.extends,
     old.sender as receiver
 from main.posts as old, rows as r
-WHERE old.id = r.extends
\ No newline at end of file
+WHERE old.id = r.extends
@@ -1,3 +1,21 @@
-DELETE FROM main.posts
-WHERE id = $1 AND sender = $2
-RETURNING id, extends;
\ No newline at end of file
+WITH deleted AS (
+    UPDATE main.posts
+    SET deleted_at = CURRENT_TIMESTAMP, content = '', mentions = '[]', revisions = 0
+    WHERE id = $1 AND sender = $2 AND deleted_at IS NULL
+    RETURNING id, extends
+), revisions AS (
+    -- 历史版本也是内容，和内容一起删除
+    DELETE FROM main.post_revisions AS r
+    USING deleted AS d
+    WHERE r.post_id = d.id
+), tags AS (
+    -- 删除后不再出现在标签下，hour 是标签计入热门统计的小时
+    DELETE FROM main.post_tags AS t
+    USING deleted AS d
+    WHERE t.post_id = d.id
+    RETURNING t.tag, floor(extract(epoch FROM t.create_time::timestamptz) / 3600)::bigint AS hour
+)
+-- 每个标签一行，没有标签时 tag 为空
+SELECT d.id, d.extends, t.tag, t.hour
+FROM deleted AS d
+LEFT JOIN tags AS t ON TRUE;
@@ -0,0 +1,13 @@
+WITH old AS (
+    SELECT id, content, mentions FROM main.posts
+    WHERE id = $1 AND sender = $2 AND deleted_at IS NULL
+    FOR UPDATE
+), revision AS (
+    INSERT INTO main.post_revisions (post_id, content)
+    SELECT id, content FROM old
+)
+UPDATE main.posts AS p
+SET content = $3, mentions = $4, edited_at = CURRENT_TIMESTAMP, revisions = p.revisions + 1
+FROM old
+WHERE p.id = old.id
+RETURNING p.id, p.edited_at, p.revisions, old.mentions AS old_mentions;
@@ -5,9 +5,14 @@ SELECT
     r1.likes, 
     r1.hates,
     r1.comments, 
+    r1.edited_at,
+    r1.mentions,
+    r1.revisions,
+    r1.deleted_at,
     r1.sender_nick, 
     r1.sender_id,
     r1.extends as origin_id,
+    r1.root_id,
     p1.content::varchar(200) AS origin_content,
     p1.create_time AS origin_create_time,
     u1.nick AS origin_sender_nick,
@@ -19,8 +24,13 @@ FROM (
         p.create_time, 
         p.likes, 
         p.comments, 
+        p.edited_at,
+        p.mentions,
+        p.revisions,
+        p.deleted_at,
         p.hates,
         p.extends, 
+        p.root_id,
         u.nick AS sender_nick, 
         u.id AS sender_id
     FROM main.posts AS p, main.users AS u 
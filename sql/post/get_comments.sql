@@ -5,10 +5,20 @@ SELECT
     p.likes, 
     p.hates,
     p.comments,
+    p.edited_at,
+    p.mentions,
+    p.revisions,
+    p.deleted_at,
     u.nick AS sender_nick, 
     u.id AS sender_id
 FROM main.posts AS p
 LEFT JOIN main.users u ON u.id = p.sender
 WHERE p.extends = $1
-ORDER BY p.create_time DESC
-LIMIT $2 OFFSET $3;
\ No newline at end of file
+ORDER BY
+    -- 2 最多点赞
+    CASE WHEN $4::smallint = 2 THEN p.likes END DESC,
+    -- 1 最早
+    CASE WHEN $4::smallint = 1 THEN p.id END ASC,
+    -- 0 最新
+    p.id DESC
+LIMIT $2 OFFSET $3;
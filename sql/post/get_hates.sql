@@ -0,0 +1 @@
+SELECT user_id FROM main.post_hates WHERE post_id = $1;
@@ -0,0 +1,43 @@
+SELECT
+    r1.id, 
+    r1.content, 
+    r1.create_time, 
+    r1.likes, 
+    r1.hates,
+    r1.comments, 
+    r1.edited_at,
+    r1.mentions,
+    r1.revisions,
+    r1.deleted_at,
+    r1.sender_nick, 
+    r1.sender_id,
+    r1.extends as origin_id,
+    p1.content::varchar(100) AS origin_content,
+    p1.create_time AS origin_create_time,
+    u1.nick AS origin_sender_nick,
+    u1.id AS origin_sender_id
+FROM (
+    SELECT
+        p.id,
+        p.content,
+        p.create_time,
+        p.likes,
+        p.comments,
+        p.edited_at,
+        p.mentions,
+        p.revisions,
+        p.deleted_at,
+        p.hates,
+        p.extends,
+        u.nick AS sender_nick,
+        u.id AS sender_id
+    FROM main.posts AS p, main.users AS u 
+    WHERE p.sender = $1 and p.sender = u.id AND p.deleted_at IS NULL
+        AND p.id > $4
+    ORDER BY p.id ASC
+    LIMIT $2 OFFSET $3
+) AS r1
+LEFT JOIN main.posts p1 ON p1.id = r1.extends
+LEFT JOIN main.users u1 ON p1.sender = u1.id
+-- 从游标开始往新的取，程序里再反转
+ORDER BY r1.id ASC;
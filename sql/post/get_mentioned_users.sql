@@ -0,0 +1,3 @@
+SELECT id, nick
+FROM main.users
+WHERE nick = ANY($1);
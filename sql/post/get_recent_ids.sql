@@ -0,0 +1,3 @@
+SELECT id FROM main.posts
+WHERE create_time > CURRENT_TIMESTAMP - make_interval(days => $1) AND deleted_at IS NULL
+ORDER BY create_time DESC;
@@ -0,0 +1,4 @@
+SELECT r.id, r.content, r.create_time
+FROM main.post_revisions AS r, main.posts AS p
+WHERE r.post_id = $1 AND p.id = r.post_id AND p.deleted_at IS NULL
+ORDER BY r.id DESC;
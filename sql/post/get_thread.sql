@@ -0,0 +1,42 @@
+-- 按 root_id 一次取出整个对话，再从 $1 往下算层级
+-- path 是从第一层到自己的 id，按 path 排序就是深度优先
+WITH RECURSIVE conversation AS MATERIALIZED (
+    SELECT p.id, p.extends
+    FROM main.posts AS p
+    WHERE p.root_id = $5
+), tree AS (
+    SELECT c.id, 1 AS depth, ARRAY[c.id] AS path
+    FROM conversation AS c
+    WHERE c.extends = $1
+    UNION ALL
+    SELECT c.id, tree.depth + 1, tree.path || c.id
+    FROM conversation AS c, tree
+    WHERE c.extends = tree.id
+), page AS (
+    SELECT tree.id, tree.depth, tree.path
+    FROM tree
+    -- 游标：从游标那条回复之后继续
+    WHERE $4::bigint IS NULL OR tree.path > (SELECT t.path FROM tree AS t WHERE t.id = $4)
+    ORDER BY tree.path
+    LIMIT $2 OFFSET $3
+)
+SELECT 
+    p.id, 
+    p.create_time, 
+    p.content, 
+    p.likes, 
+    p.hates,
+    p.comments,
+    p.edited_at,
+    p.mentions,
+    p.revisions,
+    p.deleted_at,
+    p.extends AS origin_id,
+    p.root_id,
+    u.nick AS sender_nick, 
+    u.id AS sender_id,
+    page.depth
+FROM page
+JOIN main.posts AS p ON p.id = page.id
+LEFT JOIN main.users u ON u.id = p.sender
+ORDER BY page.path;
@@ -0,0 +1,4 @@
+INSERT INTO main.post_likes(post_id, user_id)
+SELECT p.id, u.id FROM main.posts AS p, main.users AS u
+WHERE p.id = $1 AND u.id = ANY($2)
+ON CONFLICT DO NOTHING;
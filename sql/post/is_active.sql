@@ -0,0 +1 @@
+SELECT id FROM main.posts WHERE id = $1 AND deleted_at IS NULL;
@@ -1,2 +1,5 @@
+-- post 已删除或用户已经不存在时跳过，不让一条变更导致整批落库失败
 INSERT INTO main.post_likes(post_id, user_id)
-VALUES ($1, $2)
\ No newline at end of file
+SELECT p.id, u.id FROM main.posts AS p, main.users AS u
+WHERE p.id = $1 AND p.deleted_at IS NULL AND u.id = $2
+ON CONFLICT DO NOTHING;
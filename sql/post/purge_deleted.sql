@@ -0,0 +1,4 @@
+DELETE FROM main.posts AS p
+WHERE p.deleted_at < CURRENT_TIMESTAMP - make_interval(days => $1)
+    AND NOT EXISTS (SELECT 1 FROM main.posts AS c WHERE c.extends = p.id)
+RETURNING p.id;
@@ -0,0 +1,51 @@
+SELECT
+    r1.id, 
+    r1.content::varchar(100), 
+    -- 用控制字符标记匹配的词，程序里转义 html 后再换成 <em>
+    ts_headline(
+        'simple', translate(r1.content, chr(2) || chr(3), ''), websearch_to_tsquery('simple', $3),
+        'StartSel=' || chr(2) || ', StopSel=' || chr(3) || ', MaxFragments=2, MaxWords=20, MinWords=5'
+    ) AS snippet,
+    r1.create_time, 
+    r1.likes, 
+    r1.hates,
+    r1.comments, 
+    r1.edited_at,
+    r1.mentions,
+    r1.revisions,
+    r1.deleted_at,
+    r1.sender_nick, 
+    r1.sender_id,
+    r1.extends as origin_id,
+    p1.content::varchar(50) AS origin_content,
+    p1.create_time AS origin_create_time,
+    u1.nick AS origin_sender_nick,
+    u1.id AS origin_sender_id
+FROM (
+    SELECT
+        p.id,
+        p.content,
+        p.create_time,
+        p.likes,
+        p.comments,
+        p.edited_at,
+        p.mentions,
+        p.revisions,
+        p.deleted_at,
+        p.hates,
+        p.extends,
+        u.nick AS sender_nick,
+        u.id AS sender_id,
+        ts_rank(p.search_vector, q) AS rank
+    FROM main.posts AS p, main.users AS u, websearch_to_tsquery('simple', $3) AS q
+    WHERE p.sender = u.id AND p.deleted_at IS NULL AND p.search_vector @@ q
+        AND ($4::integer IS NULL OR p.sender = $4)
+        AND ($5::timestamp IS NULL OR p.create_time >= $5)
+        AND ($6::timestamp IS NULL OR p.create_time < $6)
+        AND ($7::boolean IS NULL OR (p.extends IS NOT NULL) = $7)
+    ORDER BY rank DESC, p.id DESC
+    LIMIT $1 OFFSET $2
+) AS r1
+LEFT JOIN main.posts p1 ON p1.id = r1.extends
+LEFT JOIN main.users u1 ON p1.sender = u1.id
+ORDER BY r1.rank DESC, r1.id DESC;
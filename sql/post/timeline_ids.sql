@@ -0,0 +1,7 @@
+SELECT p.id FROM main.posts AS p
+WHERE p.deleted_at IS NULL AND (p.sender = $1 OR p.sender IN (
+    SELECT f.addressee_id FROM main.friendship AS f, main.users AS u
+    WHERE f.requester_id = $1 AND u.id = f.addressee_id AND u.followers <= $2
+))
+ORDER BY p.id DESC
+LIMIT $3;
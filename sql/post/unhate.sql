@@ -0,0 +1 @@
+DELETE FROM main.post_hates WHERE post_id = $1 and user_id = $2;
@@ -12,6 +12,10 @@ COMMENT ON SCHEMA main
 CREATE EXTENSION IF NOT EXISTS "pgcrypto"
     WITH SCHEMA main CASCADE;
 
+-- 模糊搜索用户
+CREATE EXTENSION IF NOT EXISTS "pg_trgm"
+    WITH SCHEMA main CASCADE;
+
 
 -- 用户表 (id自增)
 CREATE TABLE IF NOT EXISTS main.users
@@ -28,9 +32,28 @@ CREATE TABLE IF NOT EXISTS main.users
     _salt text NOT NULL,
     -- 创建时间
     create_time DATE NOT NULL DEFAULT CURRENT_DATE,
+    -- 关注者数量
+    followers integer NOT NULL DEFAULT 0,
+    -- 个人简介
+    bio character varying(160) NOT NULL DEFAULT '',
+    -- 显示名称，可以重复，nick 是唯一的账号名
+    display_name character varying(30) NOT NULL DEFAULT '',
+    -- 所在地
+    location character varying(30) NOT NULL DEFAULT '',
+    -- 个人网站
+    website character varying(100) NOT NULL DEFAULT '',
+    -- 生日
+    birthday DATE,
+    -- 资料修改时间
+    updated_at timestamp without time zone,
     CONSTRAINT users_pkey PRIMARY KEY (id)
 );
 
+-- 按昵称模糊搜索
+CREATE INDEX IF NOT EXISTS users_nick_trgm_idx ON main.users USING GIN (nick main.gin_trgm_ops);
+-- 按昵称前缀搜索（输入提示）
+CREATE INDEX IF NOT EXISTS users_nick_lower_idx ON main.users (lower(nick) text_pattern_ops);
+
 -- post表 sender外键约束，删除账号时自动删除其所有po文
 CREATE TABLE IF NOT EXISTS main.posts
 (
@@ -50,10 +73,34 @@ CREATE TABLE IF NOT EXISTS main.posts
     hates integer NOT NULL DEFAULT 0,
     -- 继承（评论） 如果原文删除，则查找不到
     extends bigint,
+    -- 所在对话的根 post，根 post 为自己
+    root_id bigint,
+    -- 最后编辑时间
+    edited_at timestamp without time zone,
+    -- 编辑次数
+    revisions integer NOT NULL DEFAULT 0,
+    -- @ 提到的用户 [{user_id, nick, start, length}]
+    mentions jsonb NOT NULL DEFAULT '[]',
+    -- 删除时间，删除后保留记录，回复仍然可以访问
+    deleted_at timestamp without time zone,
+    -- 全文搜索
+    search_vector tsvector GENERATED ALWAYS AS (to_tsvector('simple', content)) STORED,
     -- 主键约束
     CONSTRAINT posts_pkey PRIMARY KEY (id)
 );
 
+-- 查找评论
+CREATE INDEX IF NOT EXISTS posts_extends_idx ON main.posts (extends);
+
+-- 查找整个对话
+CREATE INDEX IF NOT EXISTS posts_root_idx ON main.posts (root_id);
+
+-- 查找某个用户的 post
+CREATE INDEX IF NOT EXISTS posts_sender_idx ON main.posts (sender, id DESC);
+
+-- 全文搜索
+CREATE INDEX IF NOT EXISTS posts_search_idx ON main.posts USING GIN (search_vector);
+
 -- 通知
 CREATE TABLE IF NOT EXISTS main.notices
 (
@@ -69,14 +116,17 @@ CREATE TABLE IF NOT EXISTS main.notices
     addressee_id integer NOT NULL REFERENCES main.users(id) ON DELETE CASCADE,
     -- 创建时间
     create_time timestamp without time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
-    -- -- 是否已读
-    -- read boolean NOT NULL DEFAULT FALSE,
+    -- 是否已读
+    read boolean NOT NULL DEFAULT FALSE,
     -- 主键约束
     CONSTRAINT notices_pkey PRIMARY KEY (id),
     -- 唯一约束
     UNIQUE (sender, notice_type, sender_object)
 );
 
+-- 未读通知统计
+CREATE INDEX IF NOT EXISTS notices_unread_idx ON main.notices (addressee_id, notice_type) WHERE NOT read;
+
 -- 好友关系表
 CREATE TABLE IF NOT EXISTS main.friendship
 (
@@ -88,4 +138,106 @@ CREATE TABLE IF NOT EXISTS main.friendship
     create_time DATE NOT NULL DEFAULT CURRENT_DATE,
     -- 主键约束
     CONSTRAINT friendship_pkey PRIMARY KEY (requester_id, addressee_id)
-)
\ No newline at end of file
+);
+
+-- 查找关注者
+CREATE INDEX IF NOT EXISTS friendship_addressee_idx ON main.friendship (addressee_id);
+
+-- 点赞表 post删除时自动删除
+CREATE TABLE IF NOT EXISTS main.post_likes
+(
+    -- post id
+    post_id bigint NOT NULL REFERENCES main.posts(id) ON DELETE CASCADE,
+    -- 点赞的用户
+    user_id integer NOT NULL REFERENCES main.users(id) ON DELETE CASCADE,
+    -- 创建时间
+    create_time timestamp without time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- 主键约束
+    CONSTRAINT post_likes_pkey PRIMARY KEY (post_id, user_id)
+);
+
+-- 反感表 post删除时自动删除
+CREATE TABLE IF NOT EXISTS main.post_hates
+(
+    -- post id
+    post_id bigint NOT NULL REFERENCES main.posts(id) ON DELETE CASCADE,
+    -- 反感的用户
+    user_id integer NOT NULL REFERENCES main.users(id) ON DELETE CASCADE,
+    -- 创建时间
+    create_time timestamp without time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- 主键约束
+    CONSTRAINT post_hates_pkey PRIMARY KEY (post_id, user_id)
+);
+
+-- post 的历史版本 post删除时自动删除
+CREATE TABLE IF NOT EXISTS main.post_revisions
+(
+    -- id
+    id bigserial NOT NULL,
+    -- post id
+    post_id bigint NOT NULL REFERENCES main.posts(id) ON DELETE CASCADE,
+    -- 编辑前的内容
+    content text NOT NULL,
+    -- 被替换的时间
+    create_time timestamp without time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- 主键约束
+    CONSTRAINT post_revisions_pkey PRIMARY KEY (id)
+);
+
+-- 查找 post 的历史版本
+CREATE INDEX IF NOT EXISTS post_revisions_post_idx ON main.post_revisions (post_id);
+
+-- 关闭的通知类型
+CREATE TABLE IF NOT EXISTS main.notice_preferences
+(
+    -- 用户
+    user_id integer NOT NULL REFERENCES main.users(id) ON DELETE CASCADE,
+    -- 通知类型
+    notice_type smallint NOT NULL,
+    -- 是否接收，没有记录时接收
+    enabled boolean NOT NULL DEFAULT TRUE,
+    -- 主键约束
+    CONSTRAINT notice_preferences_pkey PRIMARY KEY (user_id, notice_type)
+);
+
+-- 屏蔽的用户，不接收这个用户的通知
+CREATE TABLE IF NOT EXISTS main.notice_muted_users
+(
+    -- 用户
+    user_id integer NOT NULL REFERENCES main.users(id) ON DELETE CASCADE,
+    -- 被屏蔽的用户
+    muted_id integer NOT NULL REFERENCES main.users(id) ON DELETE CASCADE,
+    -- 创建时间
+    create_time timestamp without time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- 主键约束
+    CONSTRAINT notice_muted_users_pkey PRIMARY KEY (user_id, muted_id)
+);
+
+-- 屏蔽的 post，不接收这个 post 和它的回复的通知
+CREATE TABLE IF NOT EXISTS main.notice_muted_posts
+(
+    -- 用户
+    user_id integer NOT NULL REFERENCES main.users(id) ON DELETE CASCADE,
+    -- 被屏蔽的 post
+    post_id bigint NOT NULL REFERENCES main.posts(id) ON DELETE CASCADE,
+    -- 创建时间
+    create_time timestamp without time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- 主键约束
+    CONSTRAINT notice_muted_posts_pkey PRIMARY KEY (user_id, post_id)
+);
+
+-- post 的话题标签
+CREATE TABLE IF NOT EXISTS main.post_tags
+(
+    -- post
+    post_id bigint NOT NULL REFERENCES main.posts(id) ON DELETE CASCADE,
+    -- 标签（小写，不包括 #）
+    tag character varying(32) NOT NULL,
+    -- 创建时间
+    create_time timestamp without time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    -- 主键约束
+    CONSTRAINT post_tags_pkey PRIMARY KEY (post_id, tag)
+);
+
+-- 按标签查找 post
+CREATE INDEX IF NOT EXISTS post_tags_tag_idx ON main.post_tags (tag, post_id DESC);
@@ -0,0 +1,3 @@
+SELECT id, sender, content, create_time, extends
+FROM main.posts
+WHERE id = $1 AND deleted_at IS NULL;
@@ -0,0 +1,5 @@
+SELECT id, sender, content, create_time, extends
+FROM main.posts
+WHERE id > $1 AND deleted_at IS NULL
+ORDER BY id
+LIMIT $2;
@@ -0,0 +1,5 @@
+SELECT id, nick
+FROM main.users
+WHERE id > $1
+ORDER BY id
+LIMIT $2;
@@ -0,0 +1,17 @@
+-- 返回新增和移除的标签，hour 是标签计入热门统计的小时
+WITH removed AS (
+    DELETE FROM main.post_tags
+    WHERE post_id = $1 AND tag <> ALL($2)
+    RETURNING tag, create_time
+), added AS (
+    INSERT INTO main.post_tags (post_id, tag)
+    SELECT $1, t
+    FROM unnest($2::varchar[]) AS t
+    ON CONFLICT DO NOTHING
+    RETURNING tag, create_time
+)
+SELECT tag, TRUE AS added, floor(extract(epoch FROM create_time::timestamptz) / 3600)::bigint AS hour
+FROM added
+UNION ALL
+SELECT tag, FALSE AS added, floor(extract(epoch FROM create_time::timestamptz) / 3600)::bigint AS hour
+FROM removed;
@@ -27,4 +27,60 @@ CREATE OR REPLACE TRIGGER post_comments_updator
 AFTER INSERT OR UPDATE OR DELETE
 ON main.posts
 FOR EACH ROW
-EXECUTE PROCEDURE post_comments_update_func();
\ No newline at end of file
+EXECUTE PROCEDURE post_comments_update_func();
+CREATE OR REPLACE FUNCTION post_likes_update_func() RETURNS TRIGGER AS
+$$
+    BEGIN
+        IF (TG_OP = 'DELETE') THEN
+            UPDATE main.posts SET likes = likes - 1 WHERE id = OLD.post_id;
+        ELSEIF (TG_OP = 'INSERT') THEN
+            UPDATE main.posts SET likes = likes + 1 WHERE id = NEW.post_id;
+        END IF;
+        RETURN NULL;
+    END
+$$
+LANGUAGE plpgsql;
+
+CREATE OR REPLACE TRIGGER post_likes_updator
+AFTER INSERT OR DELETE
+ON main.post_likes
+FOR EACH ROW
+EXECUTE PROCEDURE post_likes_update_func();
+
+CREATE OR REPLACE FUNCTION post_hates_update_func() RETURNS TRIGGER AS
+$$
+    BEGIN
+        IF (TG_OP = 'DELETE') THEN
+            UPDATE main.posts SET hates = hates - 1 WHERE id = OLD.post_id;
+        ELSEIF (TG_OP = 'INSERT') THEN
+            UPDATE main.posts SET hates = hates + 1 WHERE id = NEW.post_id;
+        END IF;
+        RETURN NULL;
+    END
+$$
+LANGUAGE plpgsql;
+
+CREATE OR REPLACE TRIGGER post_hates_updator
+AFTER INSERT OR DELETE
+ON main.post_hates
+FOR EACH ROW
+EXECUTE PROCEDURE post_hates_update_func();
+
+CREATE OR REPLACE FUNCTION user_followers_update_func() RETURNS TRIGGER AS
+$$
+    BEGIN
+        IF (TG_OP = 'DELETE') THEN
+            UPDATE main.users SET followers = followers - 1 WHERE id = OLD.addressee_id;
+        ELSEIF (TG_OP = 'INSERT') THEN
+            UPDATE main.users SET followers = followers + 1 WHERE id = NEW.addressee_id;
+        END IF;
+        RETURN NULL;
+    END
+$$
+LANGUAGE plpgsql;
+
+CREATE OR REPLACE TRIGGER user_followers_updator
+AFTER INSERT OR DELETE
+ON main.friendship
+FOR EACH ROW
+EXECUTE PROCEDURE user_followers_update_func();
@@ -0,0 +1,20 @@
+SELECT
+    u.id,
+    u.nick,
+    u.avatar_url,
+    f1.requester_id IS NOT NULL AS following,
+    f2.requester_id IS NOT NULL AS followed_by
+FROM main.users AS u
+LEFT JOIN main.friendship f1 ON f1.requester_id = $2 AND f1.addressee_id = u.id
+LEFT JOIN main.friendship f2 ON f2.requester_id = u.id AND f2.addressee_id = $2
+WHERE u.nick ILIKE $3 OR u.nick OPERATOR(main.%) $1
+ORDER BY
+    -- 完全匹配 > 前缀匹配 > 其他
+    lower(u.nick) = lower($1) DESC,
+    u.nick ILIKE $4 DESC,
+    -- 同一级里 互相关注 > 我关注的 > 其他
+    f1.requester_id IS NOT NULL AND f2.requester_id IS NOT NULL DESC,
+    f1.requester_id IS NOT NULL DESC,
+    main.similarity(u.nick, $1) DESC,
+    u.id
+LIMIT $5;
@@ -0,0 +1,20 @@
+SELECT
+    u.id,
+    u.nick,
+    u.avatar_url,
+    f1.requester_id IS NOT NULL AS following,
+    f2.requester_id IS NOT NULL AS followed_by
+FROM main.users AS u
+LEFT JOIN main.friendship f1 ON f1.requester_id = $2 AND f1.addressee_id = u.id
+LEFT JOIN main.friendship f2 ON f2.requester_id = u.id AND f2.addressee_id = $2
+-- 关键字太短时只做前缀匹配，走 users_nick_lower_idx
+WHERE lower(u.nick) LIKE $3
+ORDER BY
+    -- 完全匹配 > 前缀匹配
+    lower(u.nick) = lower($1) DESC,
+    -- 同一级里 互相关注 > 我关注的 > 其他
+    f1.requester_id IS NOT NULL AND f2.requester_id IS NOT NULL DESC,
+    f1.requester_id IS NOT NULL DESC,
+    char_length(u.nick),
+    u.id
+LIMIT $4;
@@ -1 +1 @@
-UPDATE main.users SET nick = $1 WHERE id = $2 RETURNING nick;
\ No newline at end of file
+UPDATE main.users SET nick = $1, updated_at = CURRENT_TIMESTAMP WHERE id = $2 RETURNING nick;
\ No newline at end of file
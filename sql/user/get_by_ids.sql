@@ -0,0 +1,3 @@
+SELECT id, nick, avatar_url
+FROM main.users
+WHERE id = ANY($1);
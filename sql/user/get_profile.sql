@@ -0,0 +1,22 @@
+SELECT
+    u.id,
+    u.nick,
+    u.avatar_url,
+    u.bio,
+    u.display_name,
+    u.location,
+    u.website,
+    u.birthday,
+    u.updated_at,
+    u.create_time,
+    u.followers,
+    (SELECT count(*) FROM main.posts AS p WHERE p.sender = u.id AND p.deleted_at IS NULL) AS posts,
+    (SELECT count(*) FROM main.friendship AS f WHERE f.requester_id = u.id) AS following_count,
+    EXISTS (
+        SELECT 1 FROM main.friendship AS f WHERE f.requester_id = $2 AND f.addressee_id = u.id
+    ) AS following,
+    EXISTS (
+        SELECT 1 FROM main.friendship AS f WHERE f.requester_id = u.id AND f.addressee_id = $2
+    ) AS followed_by
+FROM main.users AS u
+WHERE u.id = $1;
@@ -1,4 +1,10 @@
 SELECT id, nick, avatar_url
 FROM main.users
-WHERE nick LIKE $1
-LIMIT $2 OFFSET $3;
\ No newline at end of file
+WHERE nick ILIKE $1
+ORDER BY
+    -- 完全匹配 > 前缀匹配 > 相似度
+    lower(nick) = lower($4) DESC,
+    nick ILIKE $5 DESC,
+    main.similarity(nick, $4) DESC,
+    id
+LIMIT $2 OFFSET $3;
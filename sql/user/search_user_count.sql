@@ -0,0 +1,3 @@
+SELECT count(*) AS total
+FROM main.users
+WHERE nick ILIKE $1;
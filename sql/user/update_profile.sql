@@ -0,0 +1,15 @@
+WITH old AS (
+    SELECT id, nick FROM main.users WHERE id = $1 FOR UPDATE
+)
+UPDATE main.users AS u
+SET
+    nick = COALESCE($2, u.nick),
+    display_name = COALESCE($3, u.display_name),
+    bio = COALESCE($4, u.bio),
+    location = COALESCE($5, u.location),
+    website = COALESCE($6, u.website),
+    birthday = CASE WHEN $7 THEN $8 ELSE u.birthday END,
+    updated_at = CURRENT_TIMESTAMP
+FROM old
+WHERE u.id = old.id
+RETURNING u.nick, old.nick AS old_nick;
@@ -15,6 +15,7 @@ macro_rules! define_num_enum {
                 $type,
             )*
         }
+        #[allow(dead_code)]
         impl $name {
             pub fn to_i16(&self) -> &'static i16 {
                 match self {
@@ -23,6 +24,14 @@ macro_rules! define_num_enum {
                     )*
                 }
             }
+            pub fn from_i16(num: &i16) -> Option<Self> {
+                $(
+                    if num == &$num {
+                        return Some($name::$type);
+                    }
+                )*
+                None
+            }
         }
     };
 }
\ No newline at end of file
@@ -1,49 +1,143 @@
-
 use actix_web::HttpResponse;
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 
+use crate::traits::paging_cursor::PagingCursor;
+
+use super::big_int::BigInt;
+
+/// 每页最大数量，启动时从配置设置
+static MAX_PAGE_SIZE: OnceCell<i64> = OnceCell::new();
+/// 没有设置时的每页最大数量
+pub const DEFAULT_MAX_PAGE_SIZE: i64 = 50;
+
 /// 分页数据
 #[derive(Debug, Serialize)]
 struct PagingData<T: Serialize> {
     page: i64,
+    /// 还有没有更多，按 after 翻页时表示还有没有更新的
     next: bool,
     list: Vec<T>,
+    /// 总数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total: Option<i64>,
+    /// 获取更早数据的游标
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_cursor: Option<BigInt>,
+    /// 获取更新数据的游标（列表中最新的一条）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prev_cursor: Option<BigInt>,
 }
 
 impl<T: Serialize> PagingData<T> {
     pub fn new(page: i64, next: bool, list: Vec<T>) -> Self {
-        Self { page, next, list }
+        Self { page, next, list, total: None, next_cursor: None, prev_cursor: None }
     }
 }
 
 pub struct Paging<'a> {
     /// 每页个数
-    limit: &'static i64,
+    limit: i64,
     /// 当前页数
     page: &'a i64,
     /// 偏移
     offset: i64,
+    /// 游标 获取id比这个小（更早）的
+    before: Option<i64>,
+    /// 游标 获取id比这个大（更新）的
+    after: Option<i64>,
 }
 
 impl<'a> Paging<'a> {
+    /// 设置每页最大数量，只在启动时调用一次
+    pub fn init_max_page_size(size: i64) {
+        let _ = MAX_PAGE_SIZE.set(size);
+    }
     pub fn default(page: &'a i64) -> Result<Self, actix_web::Error> {
         /// 每页的数量
         const COUNT_PER_PAGE: i64 = 20;
-        Ok(Self::new(&COUNT_PER_PAGE, page)?)
+        Self::new(COUNT_PER_PAGE, page)
     }
-    pub fn new(limit: &'static i64, page: &'a i64) -> Result<Self, actix_web::Error> {
+    pub fn new(limit: i64, page: &'a i64) -> Result<Self, actix_web::Error> {
         if page <= &0 {
             Err(actix_web::error::ErrorBadRequest("page must greater than 0"))
         } else {
-            Ok(Self { limit, page, offset: limit * (page - 1) })
+            Ok(Self { limit, page, offset: limit * (page - 1), before: None, after: None })
+        }
+    }
+    /// 客户端指定每页数量，超过最大值时按最大值
+    pub fn sized(page: &'a i64, page_size: &Option<i64>) -> Result<Self, actix_web::Error> {
+        match page_size {
+            None => Self::default(page),
+            Some(size) if size <= &0 => {
+                Err(actix_web::error::ErrorBadRequest("page_size must greater than 0"))
+            }
+            Some(size) => {
+                let max = MAX_PAGE_SIZE.get().copied().unwrap_or(DEFAULT_MAX_PAGE_SIZE);
+                Self::new(*size.min(&max), page)
+            }
         }
     }
-    pub fn limit(&self) -> &i64 {
-        self.limit
+    /// 有游标时按游标翻页，忽略页数
+    pub fn cursor(
+        page: &'a i64,
+        page_size: &Option<i64>,
+        before: &Option<BigInt>,
+        after: &Option<BigInt>,
+    ) -> Result<Self, actix_web::Error> {
+        let mut paging = Self::sized(page, page_size)?;
+        if before.is_some() || after.is_some() {
+            paging.offset = 0;
+            paging.before = before.map(|id| *id);
+            paging.after = after.map(|id| *id);
+        }
+        Ok(paging)
+    }
+    /// 查询时多取一条，用来判断有没有下一页
+    pub fn fetch_limit(&self) -> i64 {
+        self.limit + 1
     }
     pub fn offset(&self) -> &i64 {
         &self.offset
     }
+    pub fn before(&self) -> &Option<i64> {
+        &self.before
+    }
+    pub fn after(&self) -> &Option<i64> {
+        &self.after
+    }
+    /// 是否按游标翻页
+    pub fn is_cursor(&self) -> bool {
+        self.before.is_some() || self.after.is_some()
+    }
+    /// 是否按 after 往新的翻页，这时查询结果按 id 从旧到新排列
+    pub fn is_after(&self) -> bool {
+        self.after.is_some()
+    }
+    /// 按翻页方向选择查询，返回查询和对应的游标
+    /// before 查询按 id 从新到旧，after 查询从游标开始按 id 从旧到新
+    pub fn cursor_query(&self, before: &'static str, after: &'static str) -> (&'static str, &Option<i64>) {
+        if self.is_after() {
+            (after, &self.after)
+        } else {
+            (before, &self.before)
+        }
+    }
+    /// 去掉多取的一条，返回有没有下一页
+    pub fn take_next<T>(&self, list: &mut Vec<T>) -> bool {
+        let next = list.len() as i64 > self.limit;
+        list.truncate(self.limit as usize);
+        next
+    }
+    /// 去掉多取的一条，生成分页数据
+    /// 按 after 翻页时，反转成和其他方式一样从新到旧
+    fn paging_data<T: Serialize>(&self, mut list: Vec<T>) -> PagingData<T> {
+        let next = self.take_next(&mut list);
+        if self.is_after() {
+            list.reverse();
+        }
+        PagingData::new(*self.page, next, list)
+    }
     // pub async fn get_data<T: Serialize, F>(&self, func: F) -> Result<HttpResponse, MyError>
     // where
     //     F: Fn(&Self) -> Pin<Box<dyn Future<Output = Result<Vec<T>, MyError>>>>
@@ -55,10 +149,23 @@ impl<'a> Paging<'a> {
     //     ))
     // }
     pub fn finish<T: Serialize, E>(&self, list: Vec<T>) -> Result<HttpResponse, E> {
-        let next = &(list.len() as i64) >= self.limit;
-        Ok(HttpResponse::Ok().json(
-            PagingData::new(*self.page, next, list)
-        ))
+        Ok(HttpResponse::Ok().json(self.paging_data(list)))
+    }
+    /// 同 finish，并返回总数
+    pub fn finish_with_total<T: Serialize, E>(&self, list: Vec<T>, total: i64) -> Result<HttpResponse, E> {
+        let mut data = self.paging_data(list);
+        data.total = Some(total);
+        Ok(HttpResponse::Ok().json(data))
+    }
+    /// 同 finish，并返回前后的游标
+    /// 按 after 翻页时 next 表示还有更新的，用 prev_cursor 继续取；游标本身就是更早的，总是有 next_cursor
+    pub fn finish_with_cursor<T: Serialize + PagingCursor, E>(&self, list: Vec<T>) -> Result<HttpResponse, E> {
+        let mut data = self.paging_data(list);
+        data.prev_cursor = data.list.first().map(|item| BigInt::new(item.cursor_id()));
+        if data.next || self.is_after() {
+            data.next_cursor = data.list.last().map(|item| BigInt::new(item.cursor_id()));
+        }
+        Ok(HttpResponse::Ok().json(data))
     }
 }
 
@@ -67,4 +174,24 @@ impl<'a> Paging<'a> {
 /// 通用分页请求
 pub struct GetPageDTO {
     pub page: i64,
+    /// 每页数量
+    pub page_size: Option<i64>,
+}
+
+#[derive(Deserialize, Serialize)]
+/// 支持游标的分页请求
+/// before/after 都没有时按页数翻页
+pub struct GetCursorPageDTO {
+    #[serde(default = "first_page")]
+    pub page: i64,
+    /// 每页数量
+    pub page_size: Option<i64>,
+    /// 获取比这个id更早的
+    pub before: Option<BigInt>,
+    /// 获取比这个id更新的
+    pub after: Option<BigInt>,
+}
+
+fn first_page() -> i64 {
+    1
 }
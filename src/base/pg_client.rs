@@ -1,4 +1,4 @@
-use std::{ops::Deref, pin::Pin};
+use std::{ops::{Deref, DerefMut}, pin::Pin};
 
 use actix_web::{web, FromRequest};
 use deadpool_postgres::{Client, Pool};
@@ -10,6 +10,11 @@ use crate::errors::MyError;
 pub struct PGClient(Client);
 
 impl PGClient {
+    /// 不经过请求，直接从连接池获取（后台任务用）
+    pub async fn from_pool(pool: &Pool) -> Result<Self, MyError> {
+        let client: Client = pool.get().await.map_err(MyError::PoolError)?;
+        Ok(PGClient(client))
+    }
     pub async fn prepare(&self, query: &str) -> Result<Statement, MyError> {
         self.0.prepare(query).await.map_err(MyError::PGError)
     }
@@ -56,3 +61,9 @@ impl Deref for PGClient {
         &self.0
     }
 }
+
+impl DerefMut for PGClient {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
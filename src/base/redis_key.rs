@@ -32,6 +32,20 @@ impl RedisKey {
         post_hate_count => post_id,
         /// 获取post的发送者 STRING
         post_sender => post_id,
+        /// 正在落库的一批点赞/反感变更 LIST
+        reaction_changes_batch => batch_id,
+        /// 首页时间线的 post id LIST
+        timeline => user_id,
+
+        // ============【 标签 】============
+        /// 某个小时内标签的使用次数 ZSET
+        tag_usage => hour,
+        /// 最近几个小时的热门标签（短时间缓存） ZSET
+        tag_trending => hours,
+
+        // ============【 推送 】============
+        /// 推送事件的重放缓存 STREAM
+        push_replay => user_id,
 
         // ============【 未读 】============
         // /// 所有未读总数
@@ -45,6 +59,18 @@ impl RedisKey {
         /// 未读好友添加 NUMBER
         unread_friend_add => user_id,
         /// 未读好友移除 NUMBER
-        unread_friend_remove => user_id
+        unread_friend_remove => user_id,
+        /// 未读 @ NUMBER
+        unread_mentions => user_id
+    }
+
+    /// 等待落库的点赞/反感变更 LIST
+    pub fn reaction_changes() -> String {
+        String::from("reaction_changes")
     }
-}
\ No newline at end of file
+
+    /// 推送事件的发布频道 PUBSUB
+    pub fn push_channel() -> String {
+        String::from("push")
+    }
+}
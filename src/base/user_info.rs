@@ -9,6 +9,14 @@ pub struct UserInfo {
     pub nick: String,
 }
 
+impl UserInfo {
+    /// 从 token 获取用户信息，可以带 Bearer 前缀
+    pub fn from_token(token: &str) -> Result<Self, MyError> {
+        let token = token.split("Bearer ").last().unwrap_or("");
+        validate_token(token).map(|data| data.claims.into_user_info())
+    }
+}
+
 impl FromRequest for UserInfo {
     type Error = MyError;
     type Future = futures::future::Ready<Result<Self, Self::Error>>;
@@ -20,22 +28,9 @@ impl FromRequest for UserInfo {
         fut::ready({
             let auth = req.headers().get("Authorization");
             match auth {
-                Some(val) => {
-                    let token = val
-                        .to_str()
-                        .unwrap_or("")
-                        .split("Bearer ")
-                        .collect::<Vec<&str>>()
-                        .pop()
-                        .unwrap_or("");
-                    let token_data = validate_token(token);
-                    match token_data {
-                        Ok(data) => Ok(data.claims.into_user_info()),
-                        Err(e) => Err(e)
-                    }
-                }
+                Some(val) => UserInfo::from_token(val.to_str().unwrap_or("")),
                 None => Err(MyError::JWTTokenError),
             }
         })
     }
-}
\ No newline at end of file
+}
@@ -1,8 +1,41 @@
 use serde::Deserialize;
 
+use crate::base::paging_data::DEFAULT_MAX_PAGE_SIZE;
+
 #[derive(Debug, Default, Deserialize)]
 pub struct WepoConfig {
     pub server_addr: String,
     pub redis_addr: String,
     pub pg: deadpool_postgres::Config,
-}
\ No newline at end of file
+    /// 点赞/反感落库的间隔（秒）
+    #[serde(default = "default_reaction_flush_secs")]
+    pub reaction_flush_secs: u64,
+    /// warm-cache 重建最近几天的 post
+    #[serde(default = "default_warm_cache_days")]
+    pub warm_cache_days: i32,
+    /// 每页最大数量
+    #[serde(default = "default_max_page_size")]
+    pub max_page_size: i64,
+    /// 已删除的 post 保留天数，超过后 purge-posts 会彻底删除
+    #[serde(default = "default_deleted_post_retention_days")]
+    pub deleted_post_retention_days: i32,
+    /// 搜索索引目录，配置后启用进程内的中文搜索，否则使用 postgres 全文搜索
+    #[serde(default)]
+    pub search_index_dir: Option<String>,
+}
+
+fn default_reaction_flush_secs() -> u64 {
+    10
+}
+
+fn default_warm_cache_days() -> i32 {
+    7
+}
+
+fn default_max_page_size() -> i64 {
+    DEFAULT_MAX_PAGE_SIZE
+}
+
+fn default_deleted_post_retention_days() -> i32 {
+    30
+}
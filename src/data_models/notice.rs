@@ -25,12 +25,14 @@ pub struct Notice {
     pub addressee_id: i32,
     /// 创建时间
     pub create_time: NaiveDateTime,
-    // /// 是否已读
-    // pub read: bool,
+    /// 是否已读
+    pub read: bool,
 }
 
 define_num_enum!{
     /// 通知类型
+    #[derive(Deserialize, Serialize)]
+    #[serde(rename_all = "snake_case")]
     NoticeType {
         /// 一个人评论了你, 评论的id
         [Comment => 1],
@@ -42,10 +44,23 @@ define_num_enum!{
         [FriendAdd => 4],
         /// 好友移除
         [FriendRemove => 5],
+        /// 在 post 中 @ 了你, post 的id
+        [Mention => 6],
     }
 }
 
 impl NoticeType {
+    /// 所有的通知类型
+    pub fn all() -> [NoticeType; 6] {
+        [
+            NoticeType::Comment,
+            NoticeType::Like,
+            NoticeType::Hate,
+            NoticeType::FriendAdd,
+            NoticeType::FriendRemove,
+            NoticeType::Mention,
+        ]
+    }
     pub fn get_notice_key(&self, user_id: &i32) -> String {
         match self {
             &NoticeType::Comment => RedisKey::unread_comments(user_id),
@@ -53,14 +68,27 @@ impl NoticeType {
             &NoticeType::Hate => RedisKey::unread_hates(user_id),
             &NoticeType::FriendAdd => RedisKey::unread_friend_add(user_id),
             &NoticeType::FriendRemove => RedisKey::unread_friend_remove(user_id),
+            &NoticeType::Mention => RedisKey::unread_mentions(user_id),
         }
     }
+    /// 修改未读数量的缓存
+    fn incr_by(&self, redis: &Addr<RedisActor>, user_id: &i32, count: i64) {
+        let key = self.get_notice_key(user_id);
+        redis.do_send(RedisCmd::eval(
+            include_str!("../../lua/msg/incr_unread.lua"),
+            vec![key],
+            vec![count.to_string()],
+        ));
+    }
     /// 增加通知的数量
     pub fn incr(&self, redis: &Addr<RedisActor>, user_id: &i32) {
-        let key = self.get_notice_key(user_id);
-        redis.do_send(RedisCmd::incr(key));
+        self.incr_by(redis, user_id, 1);
     }
-    /// 清空通知数量
+    /// 减少通知的数量，不会小于 0
+    pub fn decr(&self, redis: &Addr<RedisActor>, user_id: &i32, count: i64) {
+        self.incr_by(redis, user_id, -count);
+    }
+    /// 清空通知数量的缓存，下次获取时从数据库重新统计
     pub fn del(&self, redis: &Addr<RedisActor>, user_id: &i32) {
         let key = self.get_notice_key(user_id);
         redis.do_send(RedisCmd::del(key));
@@ -80,14 +108,16 @@ pub struct NoticeComment {
     pub content: Option<String>,
     /// 评论时间
     pub create_time: NaiveDateTime,
-    // /// 已读
-    // pub read: bool,
+    /// 已读
+    pub read: bool,
     /// 原文id
     pub origin_id: Option<BigInt>,
     /// 原文
     pub origin: Option<String>,
     /// 原文时间
     pub origin_create_time: Option<NaiveDateTime>,
+    /// 所在对话的根 post id
+    pub root_id: Option<BigInt>,
 }
 
 impl From<Row> for NoticeComment {
@@ -104,8 +134,9 @@ impl From<Row> for NoticeComment {
             origin_id: row.get("origin_id"),
             origin: row.get("origin"),
             create_time: row.get("create_time"),
-            // read: row.get("read"),
+            read: row.get("read"),
             origin_create_time: row.get("origin_create_time"),
+            root_id: row.get("root_id"),
         }
     }
 }
@@ -124,8 +155,8 @@ pub struct NoticePost {
     pub content: Option<String>,
     /// 点赞时间
     pub create_time: Option<NaiveDateTime>,
-    // /// 已读
-    // pub read: bool,
+    /// 已读
+    pub read: bool,
 }
 
 impl From<Row> for NoticePost {
@@ -140,12 +171,55 @@ impl From<Row> for NoticePost {
             post_id: row.get("sender_object"),
             content: row.get("content"),
             create_time: row.get("create_time"),
-            // read: row.get("read"),
+            read: row.get("read"),
         }
     }
 }
 
 
+/// 按 post 聚合的点赞/反感通知
+#[derive(Serialize, Deserialize)]
+pub struct NoticePostGroup {
+    /// 文章id
+    pub post_id: BigInt,
+    /// 文章内容
+    pub content: Option<String>,
+    /// 最近的几个发送者
+    pub senders: Vec<UserData>,
+    /// 总数
+    pub total: i64,
+    /// 未读数量
+    pub unread: i64,
+    /// 最新的时间
+    pub create_time: NaiveDateTime,
+    /// 时间窗口的开始，用于获取这一组的单条通知
+    pub window_start: NaiveDateTime,
+}
+
+impl From<Row> for NoticePostGroup {
+    fn from(row: Row) -> Self {
+        let ids: Vec<i32> = row.get::<_, Option<Vec<i32>>>("sender_ids").unwrap_or_default();
+        let nicks: Vec<Option<String>> = row.get::<_, Option<_>>("sender_nicks").unwrap_or_default();
+        let avatar_urls: Vec<Option<String>> =
+            row.get::<_, Option<_>>("sender_avatar_urls").unwrap_or_default();
+        let senders = ids
+            .iter()
+            .zip(nicks)
+            .zip(avatar_urls)
+            .map(|((id, nick), avatar_url)| UserData::unreference(id, nick, avatar_url))
+            .collect();
+        Self {
+            post_id: row.get("sender_object"),
+            content: row.get("content"),
+            senders,
+            total: row.get("total"),
+            unread: row.get("unread"),
+            create_time: row.get("create_time"),
+            window_start: row.get("window_start"),
+        }
+    }
+}
+
 /// 评论通知
 #[derive(Serialize, Deserialize)]
 pub struct NoticeFriend {
@@ -156,8 +230,8 @@ pub struct NoticeFriend {
     pub msg: String,
     /// 点赞时间
     pub create_time: NaiveDateTime,
-    // /// 已读
-    // pub read: bool,
+    /// 已读
+    pub read: bool,
 }
 
 impl From<Row> for NoticeFriend {
@@ -171,7 +245,34 @@ impl From<Row> for NoticeFriend {
             ),
             msg: row.get("sender_object"),
             create_time: row.get("create_time"),
-            // read: row.get("read"),
+            read: row.get("read"),
         }
     }
-}
\ No newline at end of file
+}
+
+/// 所有类型的通知
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NoticeItem {
+    Comment(NoticeComment),
+    Like(NoticePost),
+    Hate(NoticePost),
+    FriendAdd(NoticeFriend),
+    FriendRemove(NoticeFriend),
+    Mention(NoticeComment),
+}
+
+impl NoticeItem {
+    /// 按 notice_type 转换，未知的类型返回 None
+    pub fn from_row(row: Row) -> Option<Self> {
+        let notice_type = NoticeType::from_i16(&row.get("notice_type"))?;
+        Some(match notice_type {
+            NoticeType::Comment => NoticeItem::Comment(row.into()),
+            NoticeType::Like => NoticeItem::Like(row.into()),
+            NoticeType::Hate => NoticeItem::Hate(row.into()),
+            NoticeType::FriendAdd => NoticeItem::FriendAdd(row.into()),
+            NoticeType::FriendRemove => NoticeItem::FriendRemove(row.into()),
+            NoticeType::Mention => NoticeItem::Mention(row.into()),
+        })
+    }
+}
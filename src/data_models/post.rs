@@ -3,13 +3,14 @@ use actix_redis::{RedisActor, RespValue};
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use async_trait::async_trait;
-use tokio_postgres::Row;
+use tokio_postgres::{types::Json, Row};
 
 use crate::{
-    base::{big_int::BigInt, redis_key::RedisKey, user_info::UserInfo},
+    base::{big_int::BigInt, pg_client::PGClient, redis_key::RedisKey, user_info::UserInfo},
     data_models::user::*,
     errors::MyError,
-    traits::sync_cache::SyncCache,
+    handlers::PostService,
+    traits::{paging_cursor::PagingCursor, sync_cache::SyncCache},
     utils::db_helper::{RedisActorHelper, RedisCmd, RespValueRedisHelper},
 };
 
@@ -40,6 +41,21 @@ pub struct PostExtends {
     /// 转发内容的创建时间
     #[serde(skip_serializing_if = "Option::is_none")]
     pub origin_create_time: Option<NaiveDateTime>,
+    /// 所在对话的根 post id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root_id: Option<BigInt>,
+    /// 最后编辑时间
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edited_at: Option<NaiveDateTime>,
+    /// 编辑次数
+    pub revision_count: i32,
+    /// 已删除，内容已清空，只保留回复
+    pub deleted: bool,
+    /// @ 提到的用户
+    pub mentions: Vec<Mention>,
+    /// 搜索结果中高亮的片段，是转义过的 html，匹配的词用 <em> 包起来
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
 }
 
 impl From<&Row> for PostExtends {
@@ -66,43 +82,84 @@ impl From<&Row> for PostExtends {
                 row.try_get("origin_sender_avatar_url").ok(),
             ),
             origin_create_time: row.try_get("origin_create_time").ok(),
+            root_id: row.try_get("root_id").ok(),
+            edited_at: row.try_get("edited_at").ok(),
+            revision_count: row.try_get("revisions").unwrap_or(0),
+            deleted: row
+                .try_get::<_, Option<NaiveDateTime>>("deleted_at")
+                .is_ok_and(|time| time.is_some()),
+            mentions: row
+                .try_get::<_, Json<Vec<Mention>>>("mentions")
+                .map(|json| json.0)
+                .unwrap_or_default(),
+            snippet: row.try_get("snippet").ok(),
+        }
+    }
+}
+
+/// @ 提到的用户
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Mention {
+    pub user_id: i32,
+    pub nick: String,
+    /// 在内容中的开始位置（按 UTF-16 计算，和 js 的字符串下标一致，包括 @）
+    pub start: i32,
+    /// 长度（按 UTF-16 计算，包括 @）
+    pub length: i32,
+}
+
+impl PostExtends {
+    /// 从redis拉取数量和我的点赞/反感状态
+    async fn fetch_cache_data(
+        &self,
+        user_id: Option<&i32>,
+        redis_addr: &Addr<RedisActor>,
+    ) -> Result<Vec<RespValue>, MyError> {
+        let id = self.id.inner();
+        let mut vec = vec![
+            RedisCmd::get(RedisKey::post_like_count(id)),
+            RedisCmd::get(RedisKey::post_hate_count(id)),
+        ];
+        if let Some(user_id) = user_id {
+            let user_id = user_id.to_string();
+            vec.append(&mut vec![
+                // 获取我是否点赞
+                RedisCmd::sismember(RedisKey::post_likes(id), &user_id),
+                // 获取我是否反感
+                RedisCmd::sismember(RedisKey::post_hates(id), &user_id),
+            ]);
         }
+        redis_addr.exec_all(vec).await
+    }
+}
+
+impl PagingCursor for PostExtends {
+    fn cursor_id(&self) -> i64 {
+        *self.id.inner()
     }
 }
 
 #[async_trait]
 impl SyncCache for PostExtends {
     /// 把redis上的数据合并
-    /// 返回 true 表明 redis 上有对应数据
-    /// 返回 false 则表明没有
+    /// redis 上没有对应数据时，先从 postgres 重建
     async fn sync_cache_data(
         &mut self,
         user: Option<&UserInfo>,
+        client: &PGClient,
         redis_addr: &Addr<RedisActor>,
     ) -> Result<(), MyError> {
         // 拉取redis里缓存的数量
-        let id = self.id.inner();
         let user_id = user.map(|v| &v.id);
         let have_user = user_id.is_some();
-        let mut ret = redis_addr
-            .exec_all({
-                let mut vec = vec![
-                    RedisCmd::get(&RedisKey::post_like_count(&id)),
-                    RedisCmd::get(&RedisKey::post_hate_count(&id)),
-                ];
-                if have_user {
-                    let user_id = user_id.unwrap().to_string();
-                    vec.append(&mut vec![
-                        // 获取我是否点赞
-                        RedisCmd::sismember(&RedisKey::post_likes(id), &user_id),
-                        // 获取我是否反感
-                        RedisCmd::sismember(&RedisKey::post_hates(id), &user_id),
-                    ]);
-                }
-                vec
-            })
-            .await?
-            .into_iter();
+        let mut list = self.fetch_cache_data(user_id, redis_addr).await?;
+        let missing = list.iter().take(2).any(|val| matches!(val, RespValue::Nil));
+        if missing {
+            // 缓存丢失，重建后再拉取一次
+            PostService::load_reaction_cache(self.id.inner(), client, redis_addr).await?;
+            list = self.fetch_cache_data(user_id, redis_addr).await?;
+        }
+        let mut ret = list.into_iter();
 
         if let Some(val) = ret.next() {
             if let Some(num) = val.bulk_to_num() {
@@ -150,4 +207,53 @@ impl PostExtendsWithComment {
         static MAX_COMMENTS: usize = 10;
         MAX_COMMENTS
     }
-}
\ No newline at end of file
+}
+
+/// post 的历史版本
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PostRevision {
+    pub id: BigInt,
+    /// 编辑前的内容
+    pub content: String,
+    /// 被替换的时间
+    pub create_time: NaiveDateTime,
+}
+
+impl From<&Row> for PostRevision {
+    fn from(row: &Row) -> Self {
+        Self {
+            id: row.get("id"),
+            content: row.get("content"),
+            create_time: row.get("create_time"),
+        }
+    }
+}
+
+/// 对话中的一条回复
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ThreadReply {
+    #[serde(flatten)]
+    pub post: PostExtends,
+    /// 相对于请求的 post 的层级，直接回复为 1
+    pub depth: i32,
+}
+
+/// post 和它下面所有层级的回复（翻页）
+/// 回复按深度优先排列，父级的 id 在 origin_id 里
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PostThread {
+    pub post: PostExtends,
+    pub replies: Vec<ThreadReply>,
+    /// 还有没有更多回复
+    pub next: bool,
+    /// 获取下一页的游标（这一页最后一条回复）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<BigInt>,
+}
+
+impl PostThread {
+    pub fn new(post: PostExtends, replies: Vec<ThreadReply>, next: bool) -> Self {
+        let next_cursor = if next { replies.last().map(|reply| reply.post.id) } else { None };
+        Self { post, replies, next, next_cursor }
+    }
+}
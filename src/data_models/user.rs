@@ -1,8 +1,19 @@
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use tokio_pg_mapper_derive::PostgresMapper;
 use tokio_postgres::Row;
 
+/// 昵称的最大长度，和 users.nick 一致
+pub const NICK_MAX_LEN: usize = 15;
+/// 显示名称的最大长度，和 users.display_name 一致
+pub const DISPLAY_NAME_MAX_LEN: usize = 30;
+/// 个人简介的最大长度，和 users.bio 一致
+pub const BIO_MAX_LEN: usize = 160;
+/// 所在地的最大长度，和 users.location 一致
+pub const LOCATION_MAX_LEN: usize = 30;
+/// 个人网站的最大长度，和 users.website 一致
+pub const WEBSITE_MAX_LEN: usize = 100;
+
 #[derive(Debug, Deserialize, Serialize, PostgresMapper)]
 #[pg_mapper(table = "users")]
 pub struct User {
@@ -84,3 +95,56 @@ impl From<&Row> for UserData {
         )
     }
 }
+
+/// 用户主页
+#[derive(Debug, Serialize)]
+pub struct UserProfile {
+    #[serde(flatten)]
+    pub user: UserData,
+    /// 显示名称
+    pub display_name: String,
+    /// 个人简介
+    pub bio: String,
+    /// 所在地
+    pub location: String,
+    /// 个人网站
+    pub website: String,
+    /// 生日
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub birthday: Option<NaiveDate>,
+    /// 资料修改时间
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<NaiveDateTime>,
+    /// 注册日期
+    pub create_time: NaiveDate,
+    /// post 数量（不包括已删除的）
+    pub post_count: i64,
+    /// 关注者数量
+    pub follower_count: i32,
+    /// 关注的人数量
+    pub following_count: i64,
+    /// 我关注了对方
+    pub following: bool,
+    /// 对方关注了我
+    pub followed_by: bool,
+}
+
+impl From<&Row> for UserProfile {
+    fn from(row: &Row) -> Self {
+        Self {
+            user: UserData::from(row),
+            display_name: row.get("display_name"),
+            bio: row.get("bio"),
+            location: row.get("location"),
+            website: row.get("website"),
+            birthday: row.get("birthday"),
+            updated_at: row.get("updated_at"),
+            create_time: row.get("create_time"),
+            post_count: row.get("posts"),
+            follower_count: row.get("followers"),
+            following_count: row.get("following_count"),
+            following: row.get("following"),
+            followed_by: row.get("followed_by"),
+        }
+    }
+}
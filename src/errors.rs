@@ -6,6 +6,7 @@ use deadpool_postgres::PoolError;
 use derive_more::{Display, From};
 use log::info;
 use serde::{Deserialize, Serialize};
+use tantivy::TantivyError;
 use tokio_pg_mapper::Error as PGMError;
 use tokio_postgres::error::Error as PGError;
 
@@ -27,6 +28,8 @@ pub enum MyError {
     PoolError(PoolError),
     MailboxError(MailboxError),
     RedisError(RedisError),
+    /// 搜索索引错误
+    SearchError(TantivyError),
     OkError(i32),
 }
 
@@ -6,7 +6,7 @@ use crate::{
     base::{pg_client::PGClient, resp::ResultResponse, user_info::UserInfo},
     data_models::notice::NoticeType,
     errors::MyError,
-    handlers::MsgService,
+    handlers::{MsgService, PostService},
 };
 
 use super::{dto::SendFriendRequestDTO, storage};
@@ -19,6 +19,8 @@ pub async fn add_friendship(
     redis_addr: web::Data<Addr<RedisActor>>,
 ) -> Result<HttpResponse, MyError> {
     let _ = storage::add_friend(&user, &body.user_id, &client).await?;
+    // 关注的人变了，重建时间线
+    PostService::invalidate_timeline(&user.id, &redis_addr);
     spawn(async move {
         // 通知
         MsgService::send_friend_notice(
@@ -42,6 +44,8 @@ pub async fn remove_friendship(
     redis_addr: web::Data<Addr<RedisActor>>,
 ) -> Result<HttpResponse, MyError> {
     let _ = storage::remove_friend(&user, &body.user_id, &client).await?;
+    // 关注的人变了，重建时间线
+    PostService::invalidate_timeline(&user.id, &redis_addr);
     spawn(async move {
         // 通知
         MsgService::send_friend_notice(
@@ -1,3 +1,4 @@
 pub mod handler;
 pub mod dto;
-mod storage;
\ No newline at end of file
+pub mod service;
+mod storage;
@@ -0,0 +1,13 @@
+use crate::{base::pg_client::PGClient, errors::MyError};
+
+use super::storage;
+
+/// 获取关注者id，按 id 排序，从 after 之后最多取 limit 个
+pub async fn get_followers(
+    user_id: &i32,
+    after: &Option<i32>,
+    limit: &i64,
+    client: &PGClient,
+) -> Result<Vec<i32>, MyError> {
+    storage::get_followers(user_id, after, limit, client).await
+}
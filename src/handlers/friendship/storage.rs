@@ -43,3 +43,20 @@ pub async fn remove_friend(
         .map_err(MyError::PGError)?;
     Ok(())
 }
+
+/// 获取关注者id，按 id 排序，从 after 之后取
+pub async fn get_followers(
+    user_id: &i32,
+    after: &Option<i32>,
+    limit: &i64,
+    client: &PGClient,
+) -> Result<Vec<i32>, MyError> {
+    let _stmt = include_str!("../../../sql/friendship/get_followers.sql");
+    let stmt = client.prepare(_stmt).await?;
+    Ok(client
+        .query(&stmt, &[user_id, after, limit])
+        .await?
+        .iter()
+        .map(|row| row.get("requester_id"))
+        .collect())
+}
@@ -2,14 +2,18 @@ pub mod user;
 pub mod post;
 pub mod msg;
 pub mod friendship;
+pub mod tag;
 
 pub use user::handler as UserHandler;
 pub use post::handler as PostHandler;
 pub use msg::handler as MsgHandler;
 pub use friendship::handler as FriendshipHandler;
+pub use tag::handler as TagHandler;
 
 pub use user::dto as UserDTO;
 pub use post::dto as PostDTO;
 
 pub use msg::service as MsgService;
 pub use post::service as PostService;
+pub use friendship::service as FriendshipService;
+pub use tag::service as TagService;
@@ -1,9 +1,11 @@
 
 use actix_redis::{Command, RespValue};
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    base::redis_key::RedisKey,
+    base::big_int::BigInt,
+    data_models::{notice::NoticeType, user::UserData},
     utils::db_helper::{RedisCmd, RespValueRedisHelper},
 };
 
@@ -15,37 +17,59 @@ pub struct UnreadMsg {
     hates: i32,
     friend_add: i32,
     friend_remove: i32,
+    mentions: i32,
 }
 
 impl UnreadMsg {
     pub fn cmd_list(user_id: &i32) -> Vec<Command> {
-        vec![
-            RedisCmd::get(RedisKey::unread_comments(user_id)),
-            RedisCmd::get(RedisKey::unread_likes(user_id)),
-            RedisCmd::get(RedisKey::unread_hates(user_id)),
-            RedisCmd::get(RedisKey::unread_friend_add(user_id)),
-            RedisCmd::get(RedisKey::unread_friend_remove(user_id)),
-        ]
+        NoticeType::all()
+            .iter()
+            .map(|notice_type| RedisCmd::get(notice_type.get_notice_key(user_id)))
+            .collect()
+    }
+
+    /// 写入缓存
+    pub fn set_cmd_list(&self, user_id: &i32) -> Vec<Command> {
+        NoticeType::all()
+            .iter()
+            .map(|notice_type| {
+                RedisCmd::set(
+                    notice_type.get_notice_key(user_id),
+                    self.count(notice_type).to_string(),
+                )
+            })
+            .collect()
     }
-}
 
-impl FromIterator<RespValue> for UnreadMsg {
-    fn from_iter<T: IntoIterator<Item = RespValue>>(iter: T) -> Self {
-        let iter = iter.into_iter();
+    /// 从缓存读取，有缺失时返回 None
+    pub fn from_cache(list: Vec<RespValue>) -> Option<Self> {
         let mut msg = Self::default();
-        let ptr_list = [
-            &mut msg.comments,
-            &mut msg.likes,
-            &mut msg.hates,
-            &mut msg.friend_add,
-            &mut msg.friend_remove,
-        ];
-        iter.enumerate().for_each(|(idx, val)| {
-            if let Some(num) = val.bulk_to_num::<i32>() {
-                *ptr_list[idx] = num;
-            }
-        });
-        msg
+        for (notice_type, val) in NoticeType::all().iter().zip(list) {
+            *msg.count_mut(notice_type) = val.bulk_to_num::<i32>()?;
+        }
+        Some(msg)
+    }
+
+    pub fn count(&self, notice_type: &NoticeType) -> i32 {
+        match notice_type {
+            NoticeType::Comment => self.comments,
+            NoticeType::Like => self.likes,
+            NoticeType::Hate => self.hates,
+            NoticeType::FriendAdd => self.friend_add,
+            NoticeType::FriendRemove => self.friend_remove,
+            NoticeType::Mention => self.mentions,
+        }
+    }
+
+    pub fn count_mut(&mut self, notice_type: &NoticeType) -> &mut i32 {
+        match notice_type {
+            NoticeType::Comment => &mut self.comments,
+            NoticeType::Like => &mut self.likes,
+            NoticeType::Hate => &mut self.hates,
+            NoticeType::FriendAdd => &mut self.friend_add,
+            NoticeType::FriendRemove => &mut self.friend_remove,
+            NoticeType::Mention => &mut self.mentions,
+        }
     }
 }
 
@@ -57,6 +81,82 @@ impl Default for UnreadMsg {
             hates: Default::default(),
             friend_add: Default::default(),
             friend_remove: Default::default(),
+            mentions: Default::default(),
         }
     }
 }
+
+/// 标记已读
+#[derive(Deserialize, Serialize)]
+pub struct ReadNoticesDTO {
+    pub ids: Vec<BigInt>,
+}
+
+/// 全部已读
+#[derive(Deserialize, Serialize)]
+pub struct ReadAllNoticesDTO {
+    /// 为空时所有类型都标记已读
+    #[serde(rename = "type")]
+    pub notice_type: Option<NoticeType>,
+}
+
+/// WebSocket 认证
+#[derive(Deserialize, Serialize)]
+pub struct WsAuthDTO {
+    pub token: Option<String>,
+}
+
+/// 获取某一组聚合通知中的单条通知
+#[derive(Deserialize, Serialize)]
+pub struct GetGroupNoticesDTO {
+    /// like 或 hate
+    #[serde(rename = "type")]
+    pub notice_type: NoticeType,
+    pub post_id: BigInt,
+    /// 聚合通知的 window_start
+    pub window_start: NaiveDateTime,
+    pub page: i64,
+    /// 每页数量
+    pub page_size: Option<i64>,
+}
+
+/// 获取所有类型的通知
+#[derive(Deserialize, Serialize)]
+pub struct GetAllNoticesDTO {
+    pub page: i64,
+    /// 每页数量
+    pub page_size: Option<i64>,
+    /// 只获取这些类型，为空时获取所有类型
+    pub types: Option<Vec<NoticeType>>,
+}
+
+/// 通知设置
+#[derive(Serialize)]
+pub struct NoticeSettings {
+    /// 关闭的通知类型
+    pub disabled_types: Vec<NoticeType>,
+    /// 屏蔽的用户
+    pub muted_users: Vec<UserData>,
+    /// 屏蔽的 post
+    pub muted_posts: Vec<BigInt>,
+}
+
+/// 开启/关闭某个类型的通知
+#[derive(Deserialize, Serialize)]
+pub struct SetNoticeTypeDTO {
+    #[serde(rename = "type")]
+    pub notice_type: NoticeType,
+    pub enabled: bool,
+}
+
+/// 屏蔽/取消屏蔽用户
+#[derive(Deserialize, Serialize)]
+pub struct MuteUserDTO {
+    pub user_id: i32,
+}
+
+/// 屏蔽/取消屏蔽 post
+#[derive(Deserialize, Serialize)]
+pub struct MutePostDTO {
+    pub post_id: BigInt,
+}
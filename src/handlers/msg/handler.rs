@@ -1,28 +1,49 @@
 use actix::Addr;
 use actix_redis::RedisActor;
-use actix_web::{web, HttpResponse};
+use actix_web::{web, FromRequest, HttpRequest, HttpResponse};
 
 use crate::{
     base::{
         paging_data::{GetPageDTO, Paging},
         pg_client::PGClient,
+        resp::ResultResponse,
         user_info::UserInfo,
     },
-    data_models::notice::NoticeType, utils::db_helper::RedisActorHelper,
+    data_models::notice::NoticeType,
+    errors::MyError,
 };
 
-use super::{storage, dto::UnreadMsg};
+use super::{push, storage, dto::{
+    GetAllNoticesDTO, GetGroupNoticesDTO, MutePostDTO, MuteUserDTO, ReadAllNoticesDTO,
+    ReadNoticesDTO, SetNoticeTypeDTO, WsAuthDTO,
+}};
+
+/// 获取所有类型的通知
+pub async fn get_all_notices(
+    user: UserInfo,
+    body: web::Json<GetAllNoticesDTO>,
+    client: PGClient,
+) -> Result<HttpResponse, actix_web::Error> {
+    let paging = Paging::sized(&body.page, &body.page_size)?;
+    let notice_types = match &body.types {
+        Some(types) if !types.is_empty() => types.as_slice(),
+        _ => &NoticeType::all(),
+    };
+    let list = storage::get_all_notices(notice_types, &user, &paging, &client).await?;
+    let total = storage::count_all_notices(notice_types, &user, &client).await?;
+    paging.finish_with_total(list, total)
+}
 
 /// 获取评论通知
 pub async fn get_comment_notices(
     user: UserInfo,
     body: web::Json<GetPageDTO>,
     client: PGClient,
-    redis_addr: web::Data<Addr<RedisActor>>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let paging = Paging::default(&body.page)?;
-    let list = storage::get_comment_notices(&user, &paging, &client, &redis_addr).await?;
-    paging.finish(list)
+    let paging = Paging::sized(&body.page, &body.page_size)?;
+    let list = storage::get_comment_notices(&NoticeType::Comment, &user, &paging, &client).await?;
+    let total = storage::count_notices(&NoticeType::Comment, &user, &client).await?;
+    paging.finish_with_total(list, total)
 }
 
 /// 获取评论通知
@@ -30,11 +51,11 @@ pub async fn get_like_notices(
     user: UserInfo,
     body: web::Json<GetPageDTO>,
     client: PGClient,
-    redis_addr: web::Data<Addr<RedisActor>>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let paging = Paging::default(&body.page)?;
-    let list = storage::get_post_notices(&NoticeType::Like, &user, &paging, &client, &redis_addr).await?;
-    paging.finish(list)
+    let paging = Paging::sized(&body.page, &body.page_size)?;
+    let list = storage::get_post_notices(&NoticeType::Like, &user, &paging, &client).await?;
+    let total = storage::count_notices(&NoticeType::Like, &user, &client).await?;
+    paging.finish_with_total(list, total)
 }
 
 /// 获取评论通知
@@ -42,11 +63,77 @@ pub async fn get_hate_notices(
     user: UserInfo,
     body: web::Json<GetPageDTO>,
     client: PGClient,
-    redis_addr: web::Data<Addr<RedisActor>>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let paging = Paging::default(&body.page)?;
-    let list = storage::get_post_notices(&NoticeType::Hate, &user, &paging, &client, &redis_addr).await?;
-    paging.finish(list)
+    let paging = Paging::sized(&body.page, &body.page_size)?;
+    let list = storage::get_post_notices(&NoticeType::Hate, &user, &paging, &client).await?;
+    let total = storage::count_notices(&NoticeType::Hate, &user, &client).await?;
+    paging.finish_with_total(list, total)
+}
+
+/// 获取按 post 聚合的点赞通知
+pub async fn get_like_notice_groups(
+    user: UserInfo,
+    body: web::Json<GetPageDTO>,
+    client: PGClient,
+) -> Result<HttpResponse, actix_web::Error> {
+    let paging = Paging::sized(&body.page, &body.page_size)?;
+    let list = storage::get_post_notice_groups(&NoticeType::Like, &user, &paging, &client).await?;
+    let total = storage::count_post_notice_groups(&NoticeType::Like, &user, &client).await?;
+    paging.finish_with_total(list, total)
+}
+
+/// 获取按 post 聚合的反感通知
+pub async fn get_hate_notice_groups(
+    user: UserInfo,
+    body: web::Json<GetPageDTO>,
+    client: PGClient,
+) -> Result<HttpResponse, actix_web::Error> {
+    let paging = Paging::sized(&body.page, &body.page_size)?;
+    let list = storage::get_post_notice_groups(&NoticeType::Hate, &user, &paging, &client).await?;
+    let total = storage::count_post_notice_groups(&NoticeType::Hate, &user, &client).await?;
+    paging.finish_with_total(list, total)
+}
+
+/// 获取某一组聚合通知中的单条通知
+pub async fn get_group_notices(
+    user: UserInfo,
+    body: web::Json<GetGroupNoticesDTO>,
+    client: PGClient,
+) -> Result<HttpResponse, actix_web::Error> {
+    if !matches!(body.notice_type, NoticeType::Like | NoticeType::Hate) {
+        return Err(MyError::FailResultError.into());
+    }
+    let paging = Paging::sized(&body.page, &body.page_size)?;
+    let list = storage::get_group_notices(
+        &body.notice_type,
+        &user,
+        &body.post_id,
+        &body.window_start,
+        &paging,
+        &client,
+    )
+    .await?;
+    let total = storage::count_group_notices(
+        &body.notice_type,
+        &user,
+        &body.post_id,
+        &body.window_start,
+        &client,
+    )
+    .await?;
+    paging.finish_with_total(list, total)
+}
+
+/// 获取 @ 通知
+pub async fn get_mention_notices(
+    user: UserInfo,
+    body: web::Json<GetPageDTO>,
+    client: PGClient,
+) -> Result<HttpResponse, actix_web::Error> {
+    let paging = Paging::sized(&body.page, &body.page_size)?;
+    let list = storage::get_comment_notices(&NoticeType::Mention, &user, &paging, &client).await?;
+    let total = storage::count_notices(&NoticeType::Mention, &user, &client).await?;
+    paging.finish_with_total(list, total)
 }
 
 /// 获取好友添加通知
@@ -54,11 +141,11 @@ pub async fn get_add_friend_notices(
     user: UserInfo,
     body: web::Json<GetPageDTO>,
     client: PGClient,
-    redis_addr: web::Data<Addr<RedisActor>>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let paging = Paging::default(&body.page)?;
-    let list = storage::get_friend_notices(&NoticeType::FriendAdd, &user, &paging, &client, &redis_addr).await?;
-    paging.finish(list)
+    let paging = Paging::sized(&body.page, &body.page_size)?;
+    let list = storage::get_friend_notices(&NoticeType::FriendAdd, &user, &paging, &client).await?;
+    let total = storage::count_notices(&NoticeType::FriendAdd, &user, &client).await?;
+    paging.finish_with_total(list, total)
 }
 
 /// 获取好友移除通知
@@ -66,19 +153,131 @@ pub async fn get_remove_friend_notices(
     user: UserInfo,
     body: web::Json<GetPageDTO>,
     client: PGClient,
-    redis_addr: web::Data<Addr<RedisActor>>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let paging = Paging::default(&body.page)?;
-    let list = storage::get_friend_notices(&NoticeType::FriendRemove, &user, &paging, &client, &redis_addr).await?;
-    paging.finish(list)
+    let paging = Paging::sized(&body.page, &body.page_size)?;
+    let list = storage::get_friend_notices(&NoticeType::FriendRemove, &user, &paging, &client).await?;
+    let total = storage::count_notices(&NoticeType::FriendRemove, &user, &client).await?;
+    paging.finish_with_total(list, total)
 }
 
 /// 获取未读消息数量
 pub async fn get_unread_msg(
     user: UserInfo,
+    client: PGClient,
+    redis_addr: web::Data<Addr<RedisActor>>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let result = storage::get_unread(&user.id, &client, &redis_addr).await?;
+    Ok(HttpResponse::Ok().json(result))
+}
+
+/// 标记已读
+pub async fn read(
+    user: UserInfo,
+    body: web::Json<ReadNoticesDTO>,
+    client: PGClient,
+    redis_addr: web::Data<Addr<RedisActor>>,
+) -> Result<HttpResponse, actix_web::Error> {
+    storage::set_read(&user, &body.ids, &client, &redis_addr).await?;
+    Ok(HttpResponse::Ok().json(ResultResponse::succ()))
+}
+
+/// 全部标记已读
+pub async fn read_all(
+    user: UserInfo,
+    query: web::Query<ReadAllNoticesDTO>,
+    client: PGClient,
+    redis_addr: web::Data<Addr<RedisActor>>,
+) -> Result<HttpResponse, actix_web::Error> {
+    storage::set_read_all(&user, &query.notice_type, &client, &redis_addr).await?;
+    Ok(HttpResponse::Ok().json(ResultResponse::succ()))
+}
+
+/// 通知推送（WebSocket）
+/// 浏览器不能设置请求头，可以用 token 参数认证
+pub async fn ws(
+    req: HttpRequest,
+    body: web::Payload,
+    query: web::Query<WsAuthDTO>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let user = match &query.token {
+        Some(token) => UserInfo::from_token(token)?,
+        None => UserInfo::extract(&req).await?,
+    };
+    let (response, session, msg_stream) = actix_ws::handle(&req, body)?;
+    actix_web::rt::spawn(push::serve(user.id, session, msg_stream));
+    Ok(response)
+}
+
+/// 通知和新 post 的事件流（SSE），不支持 WebSocket 时使用
+/// 重连时带上 Last-Event-ID 可以补发断开期间的事件
+pub async fn stream(
+    user: UserInfo,
+    req: HttpRequest,
     redis_addr: web::Data<Addr<RedisActor>>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let list = redis_addr.exec_all(UnreadMsg::cmd_list(&user.id)).await?;
-    let result = UnreadMsg::from_iter(list);
+    let last_event_id = req
+        .headers()
+        .get("Last-Event-ID")
+        .and_then(|val| val.to_str().ok())
+        .map(String::from);
+    Ok(push::sse(user.id, last_event_id, &redis_addr).await?)
+}
+
+/// 获取通知设置
+pub async fn get_settings(
+    user: UserInfo,
+    client: PGClient,
+) -> Result<HttpResponse, actix_web::Error> {
+    let result = storage::get_settings(&user, &client).await?;
     Ok(HttpResponse::Ok().json(result))
-}
\ No newline at end of file
+}
+
+/// 开启/关闭某个类型的通知
+pub async fn set_notice_type(
+    user: UserInfo,
+    body: web::Json<SetNoticeTypeDTO>,
+    client: PGClient,
+) -> Result<HttpResponse, actix_web::Error> {
+    storage::set_notice_type(&user, &body.notice_type, &body.enabled, &client).await?;
+    Ok(HttpResponse::Ok().json(ResultResponse::succ()))
+}
+
+/// 屏蔽用户
+pub async fn mute_user(
+    user: UserInfo,
+    body: web::Json<MuteUserDTO>,
+    client: PGClient,
+) -> Result<HttpResponse, actix_web::Error> {
+    storage::mute_user(&user, &body.user_id, &client).await?;
+    Ok(HttpResponse::Ok().json(ResultResponse::succ()))
+}
+
+/// 取消屏蔽用户
+pub async fn unmute_user(
+    user: UserInfo,
+    body: web::Json<MuteUserDTO>,
+    client: PGClient,
+) -> Result<HttpResponse, actix_web::Error> {
+    storage::unmute_user(&user, &body.user_id, &client).await?;
+    Ok(HttpResponse::Ok().json(ResultResponse::succ()))
+}
+
+/// 屏蔽 post
+pub async fn mute_post(
+    user: UserInfo,
+    body: web::Json<MutePostDTO>,
+    client: PGClient,
+) -> Result<HttpResponse, actix_web::Error> {
+    storage::mute_post(&user, &body.post_id, &client).await?;
+    Ok(HttpResponse::Ok().json(ResultResponse::succ()))
+}
+
+/// 取消屏蔽 post
+pub async fn unmute_post(
+    user: UserInfo,
+    body: web::Json<MutePostDTO>,
+    client: PGClient,
+) -> Result<HttpResponse, actix_web::Error> {
+    storage::unmute_post(&user, &body.post_id, &client).await?;
+    Ok(HttpResponse::Ok().json(ResultResponse::succ()))
+}
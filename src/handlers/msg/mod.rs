@@ -2,4 +2,5 @@ pub mod handler;
 pub mod service;
 pub mod data;
 pub mod dto;
+pub mod push;
 mod storage;
\ No newline at end of file
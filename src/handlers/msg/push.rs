@@ -0,0 +1,422 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use actix::{Actor, Addr, Context, Handler, Message};
+use actix_redis::{RedisActor, RespValue};
+use actix_web::{
+    http::header,
+    rt::time::{interval, sleep},
+    web::Bytes,
+    HttpResponse,
+};
+use actix_ws::{Message as WsMessage, MessageStream, ProtocolError, Session};
+use futures::{
+    channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+    future, stream, Stream, StreamExt,
+};
+use log::info;
+use once_cell::sync::OnceCell;
+use redis_async::client::pubsub_connect;
+use serde::Serialize;
+
+use crate::{
+    base::{big_int::BigInt, redis_key::RedisKey},
+    data_models::notice::{Notice, NoticeType},
+    errors::MyError,
+    utils::db_helper::{RedisActorHelper, RedisCmd},
+};
+
+use super::dto::UnreadMsg;
+
+/// 心跳间隔
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+/// 超过这个时间没有收到客户端的消息就断开
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(30);
+/// 订阅断开后重连的间隔
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(3);
+/// 每个用户保留最近多少条事件用于重放
+const REPLAY_MAX_LEN: i64 = 100;
+/// 重放缓存的过期时间（秒）
+const REPLAY_EXPIRE_SECS: i64 = 3600;
+
+static HUB: OnceCell<Addr<NoticeHub>> = OnceCell::new();
+
+/// 推送给客户端的事件
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum PushEvent {
+    /// 新的通知
+    Notice {
+        notice_type: NoticeType,
+        notice: Notice,
+        /// 最新的未读数量
+        unread: UnreadMsg,
+    },
+    /// 关注的人发了新的 post
+    Post { post_id: BigInt, sender_id: i32 },
+}
+
+/// 发送到连接上的消息
+#[derive(Clone)]
+pub struct PushMessage {
+    /// 重放缓存中的 id
+    id: String,
+    data: String,
+}
+
+impl PushMessage {
+    /// SSE 格式
+    fn to_sse(&self) -> Bytes {
+        Bytes::from(format!("id: {}\ndata: {}\n\n", self.id, self.data))
+    }
+}
+
+/// 管理本实例上所有在线的连接，一个用户可以有多个连接
+/// 事件通过 Redis 发布，每个实例订阅后推送给自己的连接
+#[derive(Default)]
+pub struct NoticeHub {
+    next_id: usize,
+    sessions: HashMap<i32, HashMap<usize, UnboundedSender<PushMessage>>>,
+}
+
+impl NoticeHub {
+    /// 启动推送服务并订阅 Redis 频道
+    pub fn init(redis_url: &str) {
+        let hub = HUB.get_or_init(|| NoticeHub::default().start()).clone();
+        actix_web::rt::spawn(subscribe(redis_url.to_string(), hub));
+    }
+
+    /// 推送给用户的所有连接，包括其他实例上的连接
+    pub fn push(user_id: &i32, event: &PushEvent, redis_addr: &Addr<RedisActor>) {
+        match serde_json::to_string(event) {
+            Ok(data) => redis_addr.do_send(RedisCmd::eval(
+                include_str!("../../../lua/msg/push.lua"),
+                vec![RedisKey::push_replay(user_id)],
+                vec![
+                    REPLAY_MAX_LEN.to_string(),
+                    REPLAY_EXPIRE_SECS.to_string(),
+                    RedisKey::push_channel(),
+                    user_id.to_string(),
+                    data,
+                ],
+            )),
+            Err(e) => info!("serialize push event error: {}", e),
+        }
+    }
+
+    /// 注册一个连接
+    async fn connect(user_id: i32) -> Result<Connection, MyError> {
+        let hub = HUB.get().ok_or(MyError::InternalServerError)?.clone();
+        let (sender, receiver) = unbounded();
+        let id = hub
+            .send(Connect { user_id, sender })
+            .await
+            .map_err(MyError::MailboxError)?;
+        Ok(Connection {
+            hub,
+            user_id,
+            id,
+            receiver,
+        })
+    }
+}
+
+impl Actor for NoticeHub {
+    type Context = Context<Self>;
+}
+
+/// 新的连接，返回连接的 id
+#[derive(Message)]
+#[rtype(result = "usize")]
+struct Connect {
+    user_id: i32,
+    sender: UnboundedSender<PushMessage>,
+}
+
+/// 断开连接
+#[derive(Message)]
+#[rtype(result = "()")]
+struct Disconnect {
+    user_id: i32,
+    id: usize,
+}
+
+/// 推送消息给本实例上的连接
+#[derive(Message)]
+#[rtype(result = "()")]
+struct Deliver {
+    user_id: i32,
+    message: PushMessage,
+}
+
+impl Handler<Connect> for NoticeHub {
+    type Result = usize;
+
+    fn handle(&mut self, msg: Connect, _ctx: &mut Self::Context) -> Self::Result {
+        self.next_id += 1;
+        self.sessions
+            .entry(msg.user_id)
+            .or_default()
+            .insert(self.next_id, msg.sender);
+        self.next_id
+    }
+}
+
+impl Handler<Disconnect> for NoticeHub {
+    type Result = ();
+
+    fn handle(&mut self, msg: Disconnect, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(conns) = self.sessions.get_mut(&msg.user_id) {
+            conns.remove(&msg.id);
+            if conns.is_empty() {
+                self.sessions.remove(&msg.user_id);
+            }
+        }
+    }
+}
+
+impl Handler<Deliver> for NoticeHub {
+    type Result = ();
+
+    fn handle(&mut self, msg: Deliver, _ctx: &mut Self::Context) -> Self::Result {
+        if let Some(conns) = self.sessions.get_mut(&msg.user_id) {
+            // 顺便清理已经关闭的连接
+            conns.retain(|_, sender| sender.unbounded_send(msg.message.clone()).is_ok());
+            if conns.is_empty() {
+                self.sessions.remove(&msg.user_id);
+            }
+        }
+    }
+}
+
+/// 一个已注册的连接，drop 时自动注销
+struct Connection {
+    hub: Addr<NoticeHub>,
+    user_id: i32,
+    id: usize,
+    receiver: UnboundedReceiver<PushMessage>,
+}
+
+impl Connection {
+    /// 收到的消息
+    fn into_stream(self) -> impl Stream<Item = PushMessage> {
+        stream::unfold(self, |mut conn| async move {
+            let message = conn.receiver.next().await?;
+            Some((message, conn))
+        })
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.hub.do_send(Disconnect {
+            user_id: self.user_id,
+            id: self.id,
+        });
+    }
+}
+
+/// 订阅 Redis 频道，断开后自动重连
+async fn subscribe(redis_url: String, hub: Addr<NoticeHub>) {
+    loop {
+        match pubsub_connect(redis_url.clone()).await {
+            Ok(conn) => match conn.subscribe(&RedisKey::push_channel()).await {
+                Ok(mut messages) => {
+                    while let Some(Ok(msg)) = messages.next().await {
+                        if let Some(deliver) = parse_published(msg) {
+                            hub.do_send(deliver);
+                        }
+                    }
+                    info!("push subscription ended, reconnecting");
+                }
+                Err(e) => info!("subscribe push channel error: {}", e),
+            },
+            Err(e) => info!("connect redis pubsub error: {}", e),
+        }
+        sleep(RECONNECT_INTERVAL).await;
+    }
+}
+
+/// 解析发布的消息：用户id 事件id 事件
+fn parse_published(msg: RespValue) -> Option<Deliver> {
+    let text = bulk_to_string(msg)?;
+    let mut parts = text.splitn(3, ' ');
+    let user_id = parts.next()?.parse::<i32>().ok()?;
+    let id = parts.next()?.to_string();
+    let data = parts.next()?.to_string();
+    Some(Deliver {
+        user_id,
+        message: PushMessage { id, data },
+    })
+}
+
+fn bulk_to_string(value: RespValue) -> Option<String> {
+    match value {
+        RespValue::BulkString(bytes) => String::from_utf8(bytes).ok(),
+        _ => None,
+    }
+}
+
+/// 读取重放缓存中 last_id 之后的事件
+async fn replay(
+    user_id: &i32,
+    last_id: &str,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<Vec<PushMessage>, MyError> {
+    let result = redis_addr
+        .exec(RedisCmd::xread(
+            RedisKey::push_replay(user_id),
+            last_id,
+            REPLAY_MAX_LEN,
+        ))
+        .await?;
+    // [[key, [[id, [field, value]], ...]]]，没有新事件或 id 格式不对时不重放
+    let entries = match result {
+        RespValue::Array(mut streams) if !streams.is_empty() => match streams.swap_remove(0) {
+            RespValue::Array(mut stream) if stream.len() == 2 => stream.pop(),
+            _ => None,
+        },
+        _ => None,
+    };
+    let entries = match entries {
+        Some(RespValue::Array(entries)) => entries,
+        _ => return Ok(vec![]),
+    };
+    Ok(entries.into_iter().filter_map(parse_entry).collect())
+}
+
+/// 解析重放缓存中的一条：[id, [data, 事件]]
+fn parse_entry(entry: RespValue) -> Option<PushMessage> {
+    let mut entry = match entry {
+        RespValue::Array(entry) if entry.len() == 2 => entry,
+        _ => return None,
+    };
+    let mut fields = match entry.pop()? {
+        RespValue::Array(fields) if fields.len() == 2 => fields,
+        _ => return None,
+    };
+    Some(PushMessage {
+        data: bulk_to_string(fields.pop()?)?,
+        id: bulk_to_string(entry.pop()?)?,
+    })
+}
+
+/// 比较重放缓存的 id（毫秒-序号）
+fn is_after(id: &str, last_id: &str) -> bool {
+    fn parse(id: &str) -> Option<(u64, u64)> {
+        let (ms, seq) = id.split_once('-')?;
+        Some((ms.parse().ok()?, seq.parse().ok()?))
+    }
+    match (parse(id), parse(last_id)) {
+        (Some(id), Some(last_id)) => id > last_id,
+        _ => true,
+    }
+}
+
+/// 心跳
+fn heartbeat() -> impl Stream<Item = ()> {
+    stream::unfold(interval(HEARTBEAT_INTERVAL), |mut ticker| async move {
+        ticker.tick().await;
+        Some(((), ticker))
+    })
+}
+
+/// 连接上的事件
+enum ConnEvent {
+    Client(Result<WsMessage, ProtocolError>),
+    Closed,
+    Push(PushMessage),
+    Heartbeat,
+}
+
+/// 处理一个 WebSocket 连接，直到断开
+pub async fn serve(user_id: i32, mut session: Session, msg_stream: MessageStream) {
+    let conn = match NoticeHub::connect(user_id).await {
+        Ok(conn) => conn,
+        Err(_) => {
+            let _ = session.close(None).await;
+            return;
+        }
+    };
+
+    let client = msg_stream
+        .map(ConnEvent::Client)
+        .chain(stream::once(async { ConnEvent::Closed }));
+    let mut events = Box::pin(stream::select(
+        stream::select(client, conn.into_stream().map(ConnEvent::Push)),
+        heartbeat().map(|_| ConnEvent::Heartbeat),
+    ));
+
+    let mut last_heartbeat = Instant::now();
+    let reason = loop {
+        match events.next().await {
+            Some(ConnEvent::Client(Ok(msg))) => {
+                last_heartbeat = Instant::now();
+                let alive = match msg {
+                    WsMessage::Ping(bytes) => session.pong(&bytes).await.is_ok(),
+                    WsMessage::Close(reason) => break reason,
+                    // 客户端只需要接收，其他消息只当作心跳
+                    _ => true,
+                };
+                if !alive {
+                    break None;
+                }
+            }
+            Some(ConnEvent::Push(message)) => {
+                if session.text(message.data).await.is_err() {
+                    break None;
+                }
+            }
+            Some(ConnEvent::Heartbeat) => {
+                if last_heartbeat.elapsed() > CLIENT_TIMEOUT || session.ping(b"").await.is_err() {
+                    break None;
+                }
+            }
+            Some(ConnEvent::Client(Err(_))) | Some(ConnEvent::Closed) | None => break None,
+        }
+    };
+
+    // 先注销连接再关闭
+    drop(events);
+    let _ = session.close(reason).await;
+}
+
+/// SSE 事件流，带 last_event_id 时先重放之后的事件
+pub async fn sse(
+    user_id: i32,
+    last_event_id: Option<String>,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<HttpResponse, MyError> {
+    // 先注册再读取重放缓存，避免中间的事件丢失
+    let conn = NoticeHub::connect(user_id).await?;
+    let replayed = match &last_event_id {
+        Some(last_id) => replay(&user_id, last_id, redis_addr).await?,
+        None => vec![],
+    };
+    let last_id = replayed.last().map(|m| m.id.clone()).or(last_event_id);
+
+    // 跳过已经重放过的事件
+    let pushed = conn
+        .into_stream()
+        .filter(move |m| {
+            future::ready(
+                last_id
+                    .as_deref()
+                    .is_none_or(|last_id| is_after(&m.id, last_id)),
+            )
+        })
+        .map(|m| m.to_sse());
+    // 注释行作为心跳，连接断开后写入失败就会结束
+    let ping = heartbeat().map(|_| Bytes::from_static(b": ping\n\n"));
+    let body = stream::iter(replayed)
+        .map(|m| m.to_sse())
+        .chain(stream::select(pushed, ping))
+        .map(Ok::<_, MyError>);
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .streaming(body))
+}
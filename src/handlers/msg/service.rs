@@ -1,9 +1,11 @@
+use std::collections::HashSet;
+
 use actix::Addr;
 use actix_redis::RedisActor;
 
 use crate::{
     base::{big_int::BigInt, pg_client::PGClient},
-    data_models::notice::NoticeType,
+    data_models::{notice::NoticeType, post::Mention},
     errors::MyError,
     handlers::PostService,
 };
@@ -58,6 +60,31 @@ pub async fn sender_post_notice(
     Ok(())
 }
 
+/// 发送 @ 通知，不通知自己，同一个人只通知一次
+pub async fn send_mention_notices(
+    sender_id: &i32,
+    post_id: &BigInt,
+    mentions: &[Mention],
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) {
+    let mut notified = HashSet::new();
+    for mention in mentions {
+        if &mention.user_id == sender_id || !notified.insert(mention.user_id) {
+            continue;
+        }
+        let _result = storage::send_notice(
+            sender_id,
+            &NoticeType::Mention,
+            &post_id.to_string(),
+            &mention.user_id,
+            client,
+            redis_addr,
+        )
+        .await;
+    }
+}
+
 /// 发送好友添加通知
 pub async fn send_friend_notice(
     notice_type: &NoticeType,
@@ -70,15 +97,11 @@ pub async fn send_friend_notice(
     let _result = storage::send_notice(sender_id, &notice_type, &msg, receiver_id, &client, redis_addr).await;
 }
 
-/// 删除post的点赞和反感通知
+/// 删除 post 的相关通知，并减少对应的未读数量
 pub async fn delete_post_notice(
-    user_id: &i32,
+    post_id: &BigInt,
     client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
 ) -> Result<(), MyError> {
-    let _stmt = include_str!("../../../sql/msg/delete_post_notices.sql");
-    let stmt = client.prepare(_stmt).await?;
-    let _vec = client.query(&stmt, &[user_id, NoticeType::Like.to_i16(), NoticeType::Hate.to_i16()])
-        .await
-        .map_err(MyError::PGError)?;
-    Ok(())
-}
\ No newline at end of file
+    storage::delete_post_notices(post_id, client, redis_addr).await
+}
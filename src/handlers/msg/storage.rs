@@ -1,17 +1,40 @@
+use std::collections::HashMap;
+
 use actix::Addr;
 use actix_redis::RedisActor;
+use chrono::NaiveDateTime;
 use log::info;
+use tokio_pg_mapper::FromTokioPostgresRow;
 
 use crate::{
     base::{
-        paging_data::Paging, pg_client::PGClient,
+        big_int::BigInt, paging_data::Paging, pg_client::PGClient,
         user_info::UserInfo,
     },
-    data_models::notice::{NoticeComment, NoticeFriend, NoticePost, NoticeType},
+    data_models::notice::{
+        Notice, NoticeComment, NoticeFriend, NoticeItem, NoticePost, NoticePostGroup,
+        NoticeType,
+    },
+    data_models::user::UserData,
     errors::MyError,
+    utils::db_helper::RedisActorHelper,
 };
 
+use super::{
+    dto::{NoticeSettings, UnreadMsg},
+    push::{NoticeHub, PushEvent},
+};
+
+/// sender_object 为 post id 的通知类型
+fn post_notice_types() -> Vec<i16> {
+    [NoticeType::Comment, NoticeType::Like, NoticeType::Hate, NoticeType::Mention]
+        .iter()
+        .map(|t| *t.to_i16())
+        .collect()
+}
+
 /// 发送通知
+/// 接收者关闭了这个类型、屏蔽了发送者或者相关的 post 时不发送
 pub async fn send_notice(
     sender: &i32,
     notice_type: &NoticeType,
@@ -23,49 +46,223 @@ pub async fn send_notice(
     let _stmt = include_str!("../../../sql/msg/insert_notices.sql");
     let stmt = client.prepare(_stmt).await?;
 
-    let result = client
+    let post_types = post_notice_types();
+    let row = client
         .query(
             &stmt,
-            &[sender, notice_type.to_i16(), sender_object, addressee_id],
+            &[sender, notice_type.to_i16(), sender_object, addressee_id, &post_types],
         )
         .await?
-        .iter()
-        .map(|_| ())
-        .collect::<Vec<()>>()
-        .pop()
-        .ok_or(MyError::InternalServerError);
-
-    if let Err(ref e) = result {
-        info!("send notice error: {}", e);
-    } else {
-        // 增加一个未读
-        notice_type.incr(redis_addr, addressee_id);
+        .pop();
+    // 接收者关闭了这个类型或者屏蔽了
+    let row = match row {
+        Some(row) => row,
+        None => return Ok(()),
+    };
+
+    let notice = match Notice::from_row(row).map_err(MyError::PGMError) {
+        Ok(notice) => notice,
+        Err(e) => {
+            info!("send notice error: {}", e);
+            return Err(e);
+        }
+    };
+    // 增加一个未读
+    notice_type.incr(redis_addr, addressee_id);
+
+    // 推送
+    let unread = get_unread(addressee_id, client, redis_addr).await?;
+    let event = PushEvent::Notice {
+        notice_type: NoticeType::from_i16(&notice.notice_type)
+            .ok_or(MyError::InternalServerError)?,
+        notice,
+        unread,
+    };
+    NoticeHub::push(addressee_id, &event, redis_addr);
+    Ok(())
+}
+
+/// 删除 post 的评论/点赞/反感/@ 通知，以及回复这个 post 的评论通知
+pub async fn delete_post_notices(
+    post_id: &BigInt,
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<(), MyError> {
+    let _stmt = include_str!("../../../sql/msg/delete_post_notices.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let rows = client
+        .query(
+            &stmt,
+            &[
+                &post_id.to_string(),
+                post_id,
+                NoticeType::Comment.to_i16(),
+                NoticeType::Like.to_i16(),
+                NoticeType::Hate.to_i16(),
+                NoticeType::Mention.to_i16(),
+            ],
+        )
+        .await?;
+
+    // 按接收者和类型统计删除的未读通知
+    let mut counts: HashMap<(i32, i16), i64> = HashMap::new();
+    for row in rows.iter().filter(|row| !row.get::<_, bool>("read")) {
+        *counts
+            .entry((row.get("addressee_id"), row.get("notice_type")))
+            .or_default() += 1;
+    }
+    decr_unread(counts, redis_addr);
+    Ok(())
+}
+
+/// 减少未读数量
+fn decr_unread(counts: HashMap<(i32, i16), i64>, redis_addr: &Addr<RedisActor>) {
+    for ((addressee_id, notice_type), count) in counts {
+        if let Some(notice_type) = NoticeType::from_i16(&notice_type) {
+            notice_type.decr(redis_addr, &addressee_id, count);
+        }
+    }
+}
+
+/// 标记已读
+pub async fn set_read(
+    user: &UserInfo,
+    ids: &[BigInt],
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<(), MyError> {
+    let _stmt = include_str!("../../../sql/msg/set_read.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let ids: Vec<i64> = ids.iter().map(|id| *id.inner()).collect();
+    let rows = client.query(&stmt, &[&user.id, &ids]).await?;
+
+    let mut counts: HashMap<(i32, i16), i64> = HashMap::new();
+    for row in rows.iter() {
+        *counts.entry((user.id, row.get("notice_type"))).or_default() += 1;
+    }
+    decr_unread(counts, redis_addr);
+    Ok(())
+}
+
+/// 全部标记已读，notice_type 为空时所有类型都标记已读
+pub async fn set_read_all(
+    user: &UserInfo,
+    notice_type: &Option<NoticeType>,
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<(), MyError> {
+    let _stmt = include_str!("../../../sql/msg/set_read_all.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let type_num = notice_type.as_ref().map(|notice_type| *notice_type.to_i16());
+    client.execute(&stmt, &[&user.id, &type_num]).await?;
+
+    match notice_type {
+        Some(notice_type) => notice_type.del(redis_addr, &user.id),
+        None => NoticeType::all()
+            .iter()
+            .for_each(|notice_type| notice_type.del(redis_addr, &user.id)),
+    }
+    Ok(())
+}
+
+/// 获取未读数量，缓存缺失时从数据库统计
+pub async fn get_unread(
+    user_id: &i32,
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<UnreadMsg, MyError> {
+    let list = redis_addr.exec_all(UnreadMsg::cmd_list(user_id)).await?;
+    if let Some(msg) = UnreadMsg::from_cache(list) {
+        return Ok(msg);
     }
-    result
+
+    let _stmt = include_str!("../../../sql/msg/count_unread.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let rows = client.query(&stmt, &[user_id]).await?;
+    let mut msg = UnreadMsg::default();
+    for row in rows.iter() {
+        if let Some(notice_type) = NoticeType::from_i16(&row.get("notice_type")) {
+            *msg.count_mut(&notice_type) = row.get::<_, i64>("total") as i32;
+        }
+    }
+    redis_addr.do_send_all(msg.set_cmd_list(user_id));
+    Ok(msg)
+}
+
+/// 某个类型的通知总数
+pub async fn count_notices(
+    notice_type: &NoticeType,
+    user: &UserInfo,
+    client: &PGClient,
+) -> Result<i64, MyError> {
+    let _stmt = include_str!("../../../sql/msg/count_notices.sql");
+    let stmt = client.prepare(_stmt).await?;
+    Ok(client
+        .query_one(&stmt, &[notice_type.to_i16(), &user.id])
+        .await?
+        .get("total"))
 }
 
-/// 获取评论通知
+/// 获取多个类型的通知，按时间合并
+pub async fn get_all_notices<'a>(
+    notice_types: &[NoticeType],
+    user: &UserInfo,
+    paging: &Paging<'a>,
+    client: &PGClient,
+) -> Result<Vec<NoticeItem>, MyError> {
+    let _stmt = include_str!("../../../sql/msg/get_all_notices.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let types: Vec<i16> = notice_types.iter().map(|t| *t.to_i16()).collect();
+    let post_types = post_notice_types();
+    // 这些类型还需要返回原文
+    let origin_types: Vec<i16> = [NoticeType::Comment, NoticeType::Mention]
+        .iter()
+        .map(|t| *t.to_i16())
+        .collect();
+    Ok(client
+        .query(&stmt, &[
+            &user.id,
+            &types,
+            &post_types,
+            &origin_types,
+            &paging.fetch_limit(),
+            paging.offset(),
+        ])
+        .await?
+        .into_iter()
+        .filter_map(NoticeItem::from_row)
+        .collect())
+}
+
+/// 多个类型的通知总数
+pub async fn count_all_notices(
+    notice_types: &[NoticeType],
+    user: &UserInfo,
+    client: &PGClient,
+) -> Result<i64, MyError> {
+    let _stmt = include_str!("../../../sql/msg/count_all_notices.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let types: Vec<i16> = notice_types.iter().map(|t| *t.to_i16()).collect();
+    Ok(client
+        .query_one(&stmt, &[&user.id, &types])
+        .await?
+        .get("total"))
+}
+
+/// 获取评论/@ 通知
 pub async fn get_comment_notices<'a>(
+    notice_type: &NoticeType,
     user: &UserInfo,
     paging: &Paging<'a>,
     client: &PGClient,
-    redis_addr: &Addr<RedisActor>,
 ) -> Result<Vec<NoticeComment>, MyError> {
     let query = include_str!("../../../sql/msg/get_comment_notices.sql");
-    let notice_type = NoticeType::Comment;
-    let result = client.query_generics(query, &[
+    client.query_generics(query, &[
         notice_type.to_i16(),
         &user.id,
-        paging.limit(),
+        &paging.fetch_limit(),
         paging.offset(),
-    ]).await;
-
-    if result.is_ok() {
-        // 清空评论通知
-        notice_type.del(redis_addr, &user.id);
-    }
-
-    result
+    ]).await
 }
 
 /// 获取点赞/反感通知
@@ -74,20 +271,95 @@ pub async fn get_post_notices<'a>(
     user: &UserInfo,
     paging: &Paging<'a>,
     client: &PGClient,
-    redis_addr: &Addr<RedisActor>,
 ) -> Result<Vec<NoticePost>, MyError> {
     let query = include_str!("../../../sql/msg/get_post_notices.sql");
-    let result = client.query_generics(query, &[
+    client.query_generics(query, &[
         notice_type.to_i16(),
         &user.id,
-        paging.limit(),
+        &paging.fetch_limit(),
         paging.offset(),
-    ]).await;
-    if result.is_ok() {
-        // 清空点赞/反感通知
-        notice_type.del(redis_addr, &user.id);
-    }
-    result
+    ]).await
+}
+
+/// 聚合通知的时间窗口（秒）
+const NOTICE_GROUP_WINDOW_SECS: i64 = 24 * 60 * 60;
+
+/// 聚合通知返回的发送者数量
+const NOTICE_GROUP_SENDERS: i32 = 3;
+
+/// 获取按 post 聚合的点赞/反感通知
+pub async fn get_post_notice_groups<'a>(
+    notice_type: &NoticeType,
+    user: &UserInfo,
+    paging: &Paging<'a>,
+    client: &PGClient,
+) -> Result<Vec<NoticePostGroup>, MyError> {
+    let query = include_str!("../../../sql/msg/get_post_notice_groups.sql");
+    client.query_generics(query, &[
+        notice_type.to_i16(),
+        &user.id,
+        &NOTICE_GROUP_WINDOW_SECS,
+        &NOTICE_GROUP_SENDERS,
+        &paging.fetch_limit(),
+        paging.offset(),
+    ]).await
+}
+
+/// 聚合后的总数
+pub async fn count_post_notice_groups(
+    notice_type: &NoticeType,
+    user: &UserInfo,
+    client: &PGClient,
+) -> Result<i64, MyError> {
+    let _stmt = include_str!("../../../sql/msg/count_post_notice_groups.sql");
+    let stmt = client.prepare(_stmt).await?;
+    Ok(client
+        .query_one(&stmt, &[notice_type.to_i16(), &user.id, &NOTICE_GROUP_WINDOW_SECS])
+        .await?
+        .get("total"))
+}
+
+/// 获取某一组聚合通知中的单条通知
+pub async fn get_group_notices<'a>(
+    notice_type: &NoticeType,
+    user: &UserInfo,
+    post_id: &BigInt,
+    window_start: &NaiveDateTime,
+    paging: &Paging<'a>,
+    client: &PGClient,
+) -> Result<Vec<NoticePost>, MyError> {
+    let query = include_str!("../../../sql/msg/get_group_notices.sql");
+    client.query_generics(query, &[
+        notice_type.to_i16(),
+        &user.id,
+        &post_id.to_string(),
+        window_start,
+        &NOTICE_GROUP_WINDOW_SECS,
+        &paging.fetch_limit(),
+        paging.offset(),
+    ]).await
+}
+
+/// 某一组聚合通知的总数
+pub async fn count_group_notices(
+    notice_type: &NoticeType,
+    user: &UserInfo,
+    post_id: &BigInt,
+    window_start: &NaiveDateTime,
+    client: &PGClient,
+) -> Result<i64, MyError> {
+    let _stmt = include_str!("../../../sql/msg/count_group_notices.sql");
+    let stmt = client.prepare(_stmt).await?;
+    Ok(client
+        .query_one(&stmt, &[
+            notice_type.to_i16(),
+            &user.id,
+            &post_id.to_string(),
+            window_start,
+            &NOTICE_GROUP_WINDOW_SECS,
+        ])
+        .await?
+        .get("total"))
 }
 
 /// 获取好友通知
@@ -96,18 +368,95 @@ pub async fn get_friend_notices<'a>(
     user: &UserInfo,
     paging: &Paging<'a>,
     client: &PGClient,
-    redis_addr: &Addr<RedisActor>,
 ) -> Result<Vec<NoticeFriend>, MyError> {
     let query = include_str!("../../../sql/msg/get_friend_notices.sql");
-    let result = client.query_generics(query, &[
+    client.query_generics(query, &[
         notice_type.to_i16(),
         &user.id,
-        paging.limit(),
+        &paging.fetch_limit(),
         paging.offset(),
-    ]).await;
-    if result.is_ok() {
-        // 清空好友通知
-        notice_type.del(redis_addr, &user.id);
-    }
-    result
-}
\ No newline at end of file
+    ]).await
+}
+
+/// 获取通知设置
+pub async fn get_settings(user: &UserInfo, client: &PGClient) -> Result<NoticeSettings, MyError> {
+    let _stmt = include_str!("../../../sql/msg/get_disabled_notice_types.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let disabled_types = client
+        .query(&stmt, &[&user.id])
+        .await?
+        .iter()
+        .filter_map(|row| NoticeType::from_i16(&row.get("notice_type")))
+        .collect();
+
+    let _stmt = include_str!("../../../sql/msg/get_muted_users.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let muted_users = client
+        .query(&stmt, &[&user.id])
+        .await?
+        .iter()
+        .map(UserData::from)
+        .collect();
+
+    let _stmt = include_str!("../../../sql/msg/get_muted_posts.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let muted_posts = client
+        .query(&stmt, &[&user.id])
+        .await?
+        .iter()
+        .map(|row| row.get("post_id"))
+        .collect();
+
+    Ok(NoticeSettings {
+        disabled_types,
+        muted_users,
+        muted_posts,
+    })
+}
+
+/// 开启/关闭某个类型的通知
+pub async fn set_notice_type(
+    user: &UserInfo,
+    notice_type: &NoticeType,
+    enabled: &bool,
+    client: &PGClient,
+) -> Result<(), MyError> {
+    let _stmt = include_str!("../../../sql/msg/set_notice_type.sql");
+    let stmt = client.prepare(_stmt).await?;
+    client
+        .execute(&stmt, &[&user.id, notice_type.to_i16(), enabled])
+        .await?;
+    Ok(())
+}
+
+/// 屏蔽用户
+pub async fn mute_user(user: &UserInfo, muted_id: &i32, client: &PGClient) -> Result<(), MyError> {
+    let _stmt = include_str!("../../../sql/msg/mute_user.sql");
+    let stmt = client.prepare(_stmt).await?;
+    client.execute(&stmt, &[&user.id, muted_id]).await?;
+    Ok(())
+}
+
+/// 取消屏蔽用户
+pub async fn unmute_user(user: &UserInfo, muted_id: &i32, client: &PGClient) -> Result<(), MyError> {
+    let _stmt = include_str!("../../../sql/msg/unmute_user.sql");
+    let stmt = client.prepare(_stmt).await?;
+    client.execute(&stmt, &[&user.id, muted_id]).await?;
+    Ok(())
+}
+
+/// 屏蔽 post
+pub async fn mute_post(user: &UserInfo, post_id: &BigInt, client: &PGClient) -> Result<(), MyError> {
+    let _stmt = include_str!("../../../sql/msg/mute_post.sql");
+    let stmt = client.prepare(_stmt).await?;
+    client.execute(&stmt, &[&user.id, post_id]).await?;
+    Ok(())
+}
+
+/// 取消屏蔽 post
+pub async fn unmute_post(user: &UserInfo, post_id: &BigInt, client: &PGClient) -> Result<(), MyError> {
+    let _stmt = include_str!("../../../sql/msg/unmute_post.sql");
+    let stmt = client.prepare(_stmt).await?;
+    client.execute(&stmt, &[&user.id, post_id]).await?;
+    Ok(())
+}
@@ -1,12 +1,19 @@
+use std::{fmt::Display, str::FromStr};
+
 use tokio_postgres::Row;
 
-use crate::base::big_int::BigInt;
+use crate::{
+    base::{big_int::BigInt, redis_key::RedisKey},
+    data_models::post::Mention,
+};
 
 /// 评论的结果
 pub struct CommentResult {
     pub id: BigInt,
     pub extends: BigInt,
     pub receiver: i32,
+    /// @ 提到的用户
+    pub mentions: Vec<Mention>,
 }
 
 impl From<&Row> for CommentResult {
@@ -15,6 +22,133 @@ impl From<&Row> for CommentResult {
             id: row.get("id"),
             extends: row.get("extends"),
             receiver: row.get("receiver"),
+            mentions: vec![],
+        }
+    }
+}
+
+/// 对 post 的表态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Reaction {
+    Like,
+    Hate,
+}
+
+impl Reaction {
+    /// 相反的表态
+    pub fn opposite(&self) -> Self {
+        match self {
+            Reaction::Like => Reaction::Hate,
+            Reaction::Hate => Reaction::Like,
         }
     }
-}
\ No newline at end of file
+    /// redis 上的 (集合, 数量) 键
+    pub fn cache_keys(&self, post_id: &i64) -> (String, String) {
+        match self {
+            Reaction::Like => (RedisKey::post_likes(post_id), RedisKey::post_like_count(post_id)),
+            Reaction::Hate => (RedisKey::post_hates(post_id), RedisKey::post_hate_count(post_id)),
+        }
+    }
+    fn as_str(&self) -> &'static str {
+        match self {
+            Reaction::Like => "like",
+            Reaction::Hate => "hate",
+        }
+    }
+}
+
+/// 一次点赞/反感的变更
+/// 先记录在 redis 上，由后台任务批量写入 postgres
+#[derive(Debug, Clone, Copy)]
+pub struct ReactionChange {
+    pub reaction: Reaction,
+    pub post_id: i64,
+    pub user_id: i32,
+    /// true 为添加，false 为取消
+    pub added: bool,
+}
+
+impl ReactionChange {
+    pub fn new(reaction: Reaction, post_id: &i64, user_id: &i32, added: bool) -> Self {
+        Self {
+            reaction,
+            post_id: *post_id,
+            user_id: *user_id,
+            added,
+        }
+    }
+}
+
+/// 格式: like:1:{post_id}:{user_id}
+impl Display for ReactionChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}:{}",
+            self.reaction.as_str(),
+            self.added as u8,
+            self.post_id,
+            self.user_id
+        )
+    }
+}
+
+impl FromStr for ReactionChange {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let reaction = match parts.next() {
+            Some("like") => Reaction::Like,
+            Some("hate") => Reaction::Hate,
+            _ => return Err(()),
+        };
+        let added = match parts.next() {
+            Some("1") => true,
+            Some("0") => false,
+            _ => return Err(()),
+        };
+        let post_id = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+        let user_id = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+        Ok(Self { reaction, post_id, user_id, added })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_reaction_change() {
+        let change = ReactionChange::new(Reaction::Like, &42, &7, true);
+        assert_eq!(change.to_string(), "like:1:42:7");
+        let change = ReactionChange::new(Reaction::Hate, &1, &2, false);
+        assert_eq!(change.to_string(), "hate:0:1:2");
+    }
+
+    #[test]
+    fn parse_reaction_change() {
+        let change = "hate:1:1234567890123:5".parse::<ReactionChange>().unwrap();
+        assert_eq!(change.reaction, Reaction::Hate);
+        assert!(change.added);
+        assert_eq!(change.post_id, 1234567890123);
+        assert_eq!(change.user_id, 5);
+    }
+
+    #[test]
+    fn parse_formatted_reaction_change() {
+        let change = ReactionChange::new(Reaction::Like, &9, &3, false);
+        let parsed = change.to_string().parse::<ReactionChange>().unwrap();
+        assert_eq!(parsed.reaction, change.reaction);
+        assert_eq!(parsed.added, change.added);
+        assert_eq!(parsed.post_id, change.post_id);
+        assert_eq!(parsed.user_id, change.user_id);
+    }
+
+    #[test]
+    fn parse_invalid_reaction_change() {
+        for entry in ["", "love:1:1:1", "like:2:1:1", "like:1:x:1", "like:1:1", "like:1:1:abc"] {
+            assert!(entry.parse::<ReactionChange>().is_err(), "{}", entry);
+        }
+    }
+}
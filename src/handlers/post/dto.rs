@@ -1,6 +1,7 @@
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 
-use crate::{base::big_int::BigInt};
+use crate::{base::big_int::BigInt, data_models::post::Mention, define_num_enum};
 
 #[derive(Deserialize, Serialize)]
 pub struct AddPostDTO {
@@ -12,6 +13,21 @@ pub struct AddPostResultDTO {
     pub id: BigInt,
 }
 
+#[derive(Deserialize, Serialize)]
+pub struct EditPostDTO {
+    pub id: BigInt,
+    pub content: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct EditPostResultDTO {
+    pub id: BigInt,
+    pub edited_at: NaiveDateTime,
+    pub revision_count: i32,
+    /// @ 提到的用户
+    pub mentions: Vec<Mention>,
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct DelPostDTO {
     pub id: BigInt,
@@ -24,13 +40,81 @@ pub struct LikePostDTO {
 }
 
 
+/// 点赞/反感后的状态
+#[derive(Deserialize, Serialize)]
+pub struct ReactionResultDTO {
+    pub like_count: i32,
+    pub hate_count: i32,
+    pub liked: bool,
+    pub hated: bool,
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct GetPostDTO {
     pub id: i64,
 }
 
+#[derive(Deserialize, Serialize)]
+pub struct GetThreadDTO {
+    pub id: i64,
+    #[serde(default = "first_page")]
+    pub page: i64,
+    /// 每页数量
+    pub page_size: Option<i64>,
+    /// 游标 上一页最后一条回复的id
+    pub after: Option<BigInt>,
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct CommentPostDTO {
     pub content: String,
     pub origin_id: BigInt,
-}
\ No newline at end of file
+}
+
+define_num_enum! {
+    /// 评论排序
+    #[derive(Deserialize, Serialize, Default)]
+    #[serde(rename_all = "snake_case")]
+    CommentSort {
+        /// 最新
+        #[default]
+        [Newest => 0],
+        /// 最早
+        [Oldest => 1],
+        /// 最多点赞
+        [MostLiked => 2],
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct GetCommentsDTO {
+    pub id: BigInt,
+    #[serde(default = "first_page")]
+    pub page: i64,
+    /// 每页数量
+    pub page_size: Option<i64>,
+    #[serde(default)]
+    pub sort: CommentSort,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct SearchPostDTO {
+    /// 关键词，支持 "短语"、or、-排除
+    pub keyword: String,
+    /// 发送者
+    pub sender_id: Option<i32>,
+    /// 开始时间（包括）
+    pub since: Option<NaiveDateTime>,
+    /// 结束时间（不包括）
+    pub until: Option<NaiveDateTime>,
+    /// 是否评论，不传时都搜索
+    pub is_comment: Option<bool>,
+    #[serde(default = "first_page")]
+    pub page: i64,
+    /// 每页数量
+    pub page_size: Option<i64>,
+}
+
+fn first_page() -> i64 {
+    1
+}
@@ -0,0 +1,192 @@
+use actix::Addr;
+use actix_redis::RedisActor;
+use redis_async::resp::FromResp;
+
+use crate::{
+    base::{big_int::BigInt, paging_data::Paging, pg_client::PGClient, redis_key::RedisKey},
+    errors::MyError,
+    handlers::{
+        msg::push::{NoticeHub, PushEvent},
+        FriendshipService,
+    },
+    utils::db_helper::{RedisActorHelper, RedisCmd, RespValueRedisHelper},
+};
+
+/// 时间线缓存的最大长度，超出的部分直接查库
+const TIMELINE_MAX_LEN: i64 = 800;
+
+/// 重建出空时间线时写入的占位 id，避免每次读取都重建
+/// 雪花id不会是 0，读取时过滤掉
+const TIMELINE_EMPTY_MARK: i64 = 0;
+
+/// 关注者超过这个数量不再推送，由关注者读取时拉取
+const FANOUT_MAX_FOLLOWERS: i32 = 1000;
+
+/// 发送新 post 事件时每次取出的关注者数量
+const FOLLOWERS_PAGE_SIZE: i64 = 1000;
+
+/// 把新 post 推送到自己和关注者的时间线
+/// 只推送到已经存在的时间线，不存在的读取时再重建
+/// 关注者太多时不推送到时间线，但新 post 事件仍然分批发送给所有关注者
+pub async fn fanout(
+    sender_id: &i32,
+    post_id: &i64,
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<(), MyError> {
+    let event = PushEvent::Post {
+        post_id: BigInt::new(*post_id),
+        sender_id: *sender_id,
+    };
+    let mut receivers = vec![];
+    let mut total = 0;
+    let mut after = None;
+    loop {
+        let followers =
+            FriendshipService::get_followers(sender_id, &after, &FOLLOWERS_PAGE_SIZE, client).await?;
+        // 通知关注者有新 post
+        for user_id in followers.iter() {
+            NoticeHub::push(user_id, &event, redis_addr);
+        }
+        total += followers.len();
+        after = followers.last().copied();
+        let done = (followers.len() as i64) < FOLLOWERS_PAGE_SIZE;
+        if total <= FANOUT_MAX_FOLLOWERS as usize {
+            receivers.extend(followers);
+        }
+        if done {
+            break;
+        }
+    }
+    if total > FANOUT_MAX_FOLLOWERS as usize {
+        // 关注者太多，改为读取时拉取
+        receivers.clear();
+    }
+    receivers.push(*sender_id);
+
+    let post_id = post_id.to_string();
+    let stop = (TIMELINE_MAX_LEN - 1).to_string();
+    redis_addr.do_send_all(
+        receivers
+            .iter()
+            .flat_map(|user_id| {
+                let key = RedisKey::timeline(user_id);
+                [
+                    RedisCmd::lpushx(&key, &post_id),
+                    RedisCmd::ltrim(&key, "0", &stop),
+                ]
+            })
+            .collect(),
+    );
+    Ok(())
+}
+
+/// 获取时间线上某一页的 post id
+/// 超出缓存长度时返回 None
+pub async fn get_timeline_ids<'a>(
+    user_id: &i32,
+    paging: &Paging<'a>,
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<Option<Vec<i64>>, MyError> {
+    // 按游标翻页时不知道游标在缓存中的位置，取出整个缓存
+    let end = if paging.is_cursor() {
+        TIMELINE_MAX_LEN
+    } else {
+        paging.offset() + paging.fetch_limit()
+    };
+    if end > TIMELINE_MAX_LEN {
+        return Ok(None);
+    }
+
+    let key = RedisKey::timeline(user_id);
+    let cached = redis_addr.exec(RedisCmd::exists(&key)).await?.integer_to_bool();
+    let mut ids = if cached {
+        let list = redis_addr
+            .exec(RedisCmd::lrange(&key, "0", (end - 1).to_string()))
+            .await?;
+        Vec::<String>::from_resp(list)
+            .map_err(|_| MyError::ParseError)?
+            .iter()
+            .filter_map(|id| id.parse::<i64>().ok())
+            .filter(|id| id != &TIMELINE_EMPTY_MARK)
+            .collect()
+    } else {
+        rebuild(user_id, client, redis_addr).await?
+    };
+
+    // 拉取关注者太多的人的 post，合并
+    let (_stmt, cursor) = paging.cursor_query(
+        include_str!("../../../sql/post/timeline_pull_ids.sql"),
+        include_str!("../../../sql/post/timeline_pull_ids_after.sql"),
+    );
+    let stmt = client.prepare(_stmt).await?;
+    let mut pulled = client
+        .query(&stmt, &[user_id, &FANOUT_MAX_FOLLOWERS, &end, cursor])
+        .await?
+        .iter()
+        .map(|row| row.get::<_, i64>("id"))
+        .collect::<Vec<i64>>();
+    // 缓存已满说明更早的 post 不在缓存里
+    let truncated = ids.len() as i64 >= TIMELINE_MAX_LEN;
+    let oldest = ids.last().copied();
+    ids.retain(|id| {
+        paging.before().is_none_or(|before| id < &before)
+            && paging.after().is_none_or(|after| id > &after)
+    });
+    if truncated {
+        let missing = match paging.after() {
+            // 游标比缓存里最早的还早，中间的 post 不在缓存里
+            Some(after) => oldest.is_some_and(|oldest| oldest > *after),
+            None => (ids.len() as i64) < paging.fetch_limit(),
+        };
+        if missing {
+            return Ok(None);
+        }
+    }
+    ids.append(&mut pulled);
+
+    // 雪花id按时间递增，按 after 翻页时从游标开始往新的取
+    if paging.is_after() {
+        ids.sort_unstable();
+    } else {
+        ids.sort_unstable_by(|a, b| b.cmp(a));
+    }
+    ids.dedup();
+    Ok(Some(
+        ids.into_iter()
+            .skip(*paging.offset() as usize)
+            .take(paging.fetch_limit() as usize)
+            .collect(),
+    ))
+}
+
+/// 从 postgres 重建时间线，返回重建后的 post id
+/// 没有 post 时也写入缓存（只有占位 id）
+async fn rebuild(
+    user_id: &i32,
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<Vec<i64>, MyError> {
+    let _stmt = include_str!("../../../sql/post/timeline_ids.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let ids = client
+        .query(&stmt, &[user_id, &FANOUT_MAX_FOLLOWERS, &TIMELINE_MAX_LEN])
+        .await?
+        .iter()
+        .map(|row| row.get::<_, i64>("id"))
+        .collect::<Vec<i64>>();
+
+    let mut values: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+    if values.is_empty() {
+        values.push(TIMELINE_EMPTY_MARK.to_string());
+    }
+    let key = RedisKey::timeline(user_id);
+    redis_addr.do_send_all(vec![RedisCmd::del(&key), RedisCmd::rpush_all(&key, values)]);
+    Ok(ids)
+}
+
+/// 关注关系变化后，删除时间线等待重建
+pub fn invalidate(user_id: &i32, redis_addr: &Addr<RedisActor>) {
+    redis_addr.del(&RedisKey::timeline(user_id));
+}
@@ -5,7 +5,7 @@ use log::info;
 
 use crate::{
     base::{
-        paging_data::{GetPageDTO, Paging},
+        paging_data::{GetCursorPageDTO, Paging},
         pg_client::PGClient,
         resp::ResultResponse,
         user_info::UserInfo,
@@ -14,6 +14,7 @@ use crate::{
     errors::MyError,
     handlers::MsgService,
     handlers::PostDTO::*,
+    search::{self, SearchIndex},
 };
 
 use super::storage;
@@ -26,12 +27,45 @@ pub async fn add(
     client: PGClient,
     redis_addr: web::Data<Addr<RedisActor>>,
 ) -> Result<HttpResponse, MyError> {
-    let post_id = storage::add(&user, &post_body, &client, &redis_addr).await?;
+    let (post_id, mentions) = storage::add(&user, &post_body, &client, &redis_addr).await?;
     info!("New Post:{}", post_id);
+    spawn(async move {
+        search::sync_post(&post_id, &client).await;
+        // @ 通知
+        MsgService::send_mention_notices(&user.id, &post_id, &mentions, &client, &redis_addr).await;
+    });
     let result = AddPostResultDTO { id: post_id };
     Ok(HttpResponse::Ok().json(result))
 }
 
+/// 编辑po
+pub async fn edit(
+    user: UserInfo,
+    body: web::Json<EditPostDTO>,
+    client: PGClient,
+    redis_addr: web::Data<Addr<RedisActor>>,
+) -> Result<HttpResponse, MyError> {
+    let (result, added) = storage::edit(&user, &body, &client, &redis_addr).await?;
+    info!("Edit Post:{}", result.id);
+    let post_id = result.id;
+    spawn(async move {
+        search::sync_post(&post_id, &client).await;
+        // 只通知新增的 @
+        MsgService::send_mention_notices(&user.id, &post_id, &added, &client, &redis_addr).await;
+    });
+    Ok(HttpResponse::Ok().json(result))
+}
+
+/// 获取po的历史版本
+pub async fn revisions(
+    _user: UserInfo,
+    body: web::Query<GetPostDTO>,
+    client: PGClient,
+) -> Result<HttpResponse, MyError> {
+    let list = storage::get_revisions(&body.id, &client).await?;
+    Ok(HttpResponse::Ok().json(list))
+}
+
 /// 删除po
 pub async fn delete(
     user: UserInfo,
@@ -41,8 +75,9 @@ pub async fn delete(
 ) -> Result<HttpResponse, MyError> {
     let _ = storage::delete(&user, &del_body, &client, &redis_addr).await?;
     spawn(async move {
+        search::sync_post(&del_body.id, &client).await;
         // 删除post的相关通知
-        let _ = MsgService::delete_post_notice(&user.id, &client).await;
+        let _ = MsgService::delete_post_notice(&del_body.id, &client, &redis_addr).await;
     });
     Ok(HttpResponse::Ok().json(ResultResponse::succ()))
 }
@@ -65,7 +100,7 @@ pub async fn like(
     redis_addr: web::Data<Addr<RedisActor>>,
     client: PGClient,
 ) -> Result<HttpResponse, Error> {
-    let _ = storage::like(&data.id, &user.id, &redis_addr).await?;
+    let result = storage::like(&data.id, &user.id, &client, &redis_addr).await?;
     spawn(async move {
         let _ = MsgService::sender_post_notice(
             &NoticeType::Like,
@@ -75,28 +110,53 @@ pub async fn like(
             &redis_addr,
         ).await;
     });
-    Ok(HttpResponse::Ok().json(ResultResponse::succ()))
+    Ok(HttpResponse::Ok().json(result))
 }
 
 /// 取消点赞
 pub async fn cancel_like(
     user: UserInfo,
     like_body: web::Query<LikePostDTO>,
+    client: PGClient,
     redis_addr: web::Data<Addr<RedisActor>>,
 ) -> Result<HttpResponse, Error> {
-    let _ = storage::cancel_like(&like_body.id, &user.id, &redis_addr).await?;
-    Ok(HttpResponse::Ok().json(ResultResponse::succ()))
+    let result = storage::cancel_like(&like_body.id, &user.id, &client, &redis_addr).await?;
+    Ok(HttpResponse::Ok().json(result))
 }
 
 /// 获取我的posts
 pub async fn mine(
     user: UserInfo,
-    body: web::Json<GetPageDTO>,
+    body: web::Json<GetCursorPageDTO>,
     client: PGClient,
     redis_addr: web::Data<Addr<RedisActor>>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let paging = Paging::default(&body.page)?;
+    let paging = Paging::cursor(&body.page, &body.page_size, &body.before, &body.after)?;
     let list = storage::get_mine(&user, &paging, &client, &redis_addr).await?;
+    paging.finish_with_cursor(list)
+}
+
+/// 获取 post 和它下面所有层级的回复（翻页）
+pub async fn thread(
+    user: UserInfo,
+    body: web::Query<GetThreadDTO>,
+    client: PGClient,
+    redis_addr: web::Data<Addr<RedisActor>>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let paging = Paging::cursor(&body.page, &body.page_size, &None, &body.after)?;
+    let thread = storage::get_thread(&user, &body.id, &paging, &client, &redis_addr).await?;
+    Ok(HttpResponse::Ok().json(thread))
+}
+
+/// 获取评论（翻页）
+pub async fn comments(
+    user: UserInfo,
+    body: web::Query<GetCommentsDTO>,
+    client: PGClient,
+    redis_addr: web::Data<Addr<RedisActor>>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let paging = Paging::sized(&body.page, &body.page_size)?;
+    let list = storage::get_comments(&user, &body.id, &body.sort, &paging, &client, &redis_addr).await?;
     paging.finish(list)
 }
 
@@ -107,7 +167,7 @@ pub async fn comment(
     client: PGClient,
     redis_addr: web::Data<Addr<RedisActor>>,
 ) -> Result<HttpResponse, MyError> {
-    let comment_result = storage::comment(&user, &body, &client).await?;
+    let comment_result = storage::comment(&user, &body, &client, &redis_addr).await?;
     info!("New Comment:{}", comment_result.id);
     // 评论成功，发送通知, 如果评论自己就不发送了
     if user.id != comment_result.receiver {
@@ -121,9 +181,20 @@ pub async fn comment(
         .await;
     }
 
-    let result = AddPostResultDTO {
-        id: comment_result.id,
-    };
+    let post_id = comment_result.id;
+    spawn(async move {
+        search::sync_post(&post_id, &client).await;
+        // @ 通知
+        MsgService::send_mention_notices(
+            &user.id,
+            &post_id,
+            &comment_result.mentions,
+            &client,
+            &redis_addr,
+        )
+        .await;
+    });
+    let result = AddPostResultDTO { id: post_id };
     Ok(HttpResponse::Ok().json(result))
 }
 
@@ -134,7 +205,7 @@ pub async fn hate(
     client: PGClient,
     redis_addr: web::Data<Addr<RedisActor>>,
 ) -> Result<HttpResponse, Error> {
-    let _ = storage::hate(&data.id, &user.id, &redis_addr).await?;
+    let result = storage::hate(&data.id, &user.id, &client, &redis_addr).await?;
     spawn(async move {
         let _ = MsgService::sender_post_notice(
             &NoticeType::Hate,
@@ -144,27 +215,58 @@ pub async fn hate(
             &redis_addr,
         ).await;
     });
-    Ok(HttpResponse::Ok().json(ResultResponse::succ()))
+    Ok(HttpResponse::Ok().json(result))
 }
 
 /// 取消反感
 pub async fn cancel_hate(
     user: UserInfo,
     like_body: web::Query<LikePostDTO>,
+    client: PGClient,
     redis_addr: web::Data<Addr<RedisActor>>,
 ) -> Result<HttpResponse, Error> {
-    let _ = storage::cancel_hate(&like_body.id, &user.id, &redis_addr).await?;
-    Ok(HttpResponse::Ok().json(ResultResponse::succ()))
+    let result = storage::cancel_hate(&like_body.id, &user.id, &client, &redis_addr).await?;
+    Ok(HttpResponse::Ok().json(result))
 }
 
 /// 浏览posts
 pub async fn browse(
     user: UserInfo,
-    body: web::Query<GetPageDTO>,
+    body: web::Query<GetCursorPageDTO>,
     client: PGClient,
     redis_addr: web::Data<Addr<RedisActor>>,
 ) -> Result<impl Responder, actix_web::Error> {
-    let paging = Paging::default(&body.page)?;
+    let paging = Paging::cursor(&body.page, &body.page_size, &body.before, &body.after)?;
     let list = storage::browse(&user, &client, &paging, &redis_addr).await?;
+    paging.finish_with_cursor(list)
+}
+
+/// 搜索posts
+pub async fn search(
+    user: UserInfo,
+    body: web::Query<SearchPostDTO>,
+    client: PGClient,
+    redis_addr: web::Data<Addr<RedisActor>>,
+) -> Result<impl Responder, actix_web::Error> {
+    if body.keyword.trim().is_empty() {
+        return Err(MyError::FailResultError.into());
+    }
+    let paging = Paging::sized(&body.page, &body.page_size)?;
+    let list = match SearchIndex::get() {
+        Some(index) => storage::search_indexed(index, &user, &body, &client, &paging, &redis_addr).await?,
+        None => storage::search(&user, &body, &client, &paging, &redis_addr).await?,
+    };
     paging.finish(list)
 }
+
+/// 浏览关注的人和我的posts
+pub async fn timeline(
+    user: UserInfo,
+    body: web::Query<GetCursorPageDTO>,
+    client: PGClient,
+    redis_addr: web::Data<Addr<RedisActor>>,
+) -> Result<impl Responder, actix_web::Error> {
+    let paging = Paging::cursor(&body.page, &body.page_size, &body.before, &body.after)?;
+    let list = storage::timeline(&user, &client, &paging, &redis_addr).await?;
+    paging.finish_with_cursor(list)
+}
@@ -2,4 +2,5 @@ pub mod handler;
 pub mod dto;
 pub mod data;
 pub mod service;
-mod storage;
\ No newline at end of file
+mod fanout;
+mod storage;
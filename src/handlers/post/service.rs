@@ -2,11 +2,12 @@ use actix::Addr;
 use actix_redis::RedisActor;
 
 use crate::{
-    base::{big_int::BigInt, pg_client::PGClient},
+    base::{big_int::BigInt, paging_data::Paging, pg_client::PGClient, user_info::UserInfo},
+    data_models::post::PostExtends,
     errors::MyError,
 };
 
-use super::storage;
+use super::{data::Reaction, fanout, storage};
 
 pub async fn get_post_sender_from_id(
     post_id: &BigInt,
@@ -15,3 +16,56 @@ pub async fn get_post_sender_from_id(
 ) -> Result<i32, MyError> {
     storage::get_post_sender_from_id(post_id, client, redis_addr).await
 }
+
+/// 把 redis 上的点赞/反感变更写入 postgres
+pub async fn flush_reaction_changes(
+    client: &mut PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<usize, MyError> {
+    storage::flush_reaction_changes(client, redis_addr).await
+}
+
+/// 从 postgres 重建 post 的点赞/反感缓存
+pub async fn load_reaction_cache(
+    post_id: &i64,
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<(), MyError> {
+    storage::load_reaction_cache(post_id, client, redis_addr).await
+}
+
+/// 把 redis 集合里的点赞/反感导入 postgres
+pub async fn import_reactions(
+    reaction: Reaction,
+    post_id: &i64,
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<u64, MyError> {
+    storage::import_reactions(reaction, post_id, client, redis_addr).await
+}
+
+/// 获取最近几天的 post id
+pub async fn get_recent_ids(days: &i32, client: &PGClient) -> Result<Vec<i64>, MyError> {
+    storage::get_recent_ids(days, client).await
+}
+
+/// 删除时间线缓存，下次读取时重建
+pub fn invalidate_timeline(user_id: &i32, redis_addr: &Addr<RedisActor>) {
+    fanout::invalidate(user_id, redis_addr)
+}
+
+/// 彻底删除超过保留天数的已删除 post
+pub async fn purge_deleted(days: &i32, client: &PGClient) -> Result<u64, MyError> {
+    storage::purge_deleted(days, client).await
+}
+
+/// 某个用户的 post
+pub async fn get_list<'a>(
+    user: &UserInfo,
+    sender_id: &i32,
+    paging: &Paging<'a>,
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<Vec<PostExtends>, MyError> {
+    storage::get_list(user, sender_id, paging, client, redis_addr).await
+}
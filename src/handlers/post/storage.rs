@@ -1,23 +1,39 @@
-use std::sync::Mutex;
+use std::{collections::HashMap, sync::Mutex};
 
 use actix::Addr;
-use actix_redis::{RedisActor};
+use actix_redis::{RedisActor, RespValue};
 use futures::future::{join_all, try_join_all};
 use log::info;
 use once_cell::sync::Lazy;
+use redis_async::resp::FromResp;
 use serde::Serialize;
 use snowflake::SnowflakeIdBucket;
+use tokio_postgres::{types::Json, Row};
+use uuid::Uuid;
 
 use crate::{
     base::{
         big_int::BigInt, paging_data::Paging, pg_client::PGClient, redis_key::RedisKey,
         user_info::UserInfo,
     },
-    data_models::post::{PostExtends, PostExtendsWithComment},
+    data_models::{
+        post::{Mention, PostExtends, PostExtendsWithComment, PostRevision, PostThread, ThreadReply},
+        user::NICK_MAX_LEN,
+    },
     errors::MyError,
-    handlers::post::{data::CommentResult, dto::*},
+    handlers::post::{
+        data::{CommentResult, Reaction, ReactionChange},
+        dto::*,
+        fanout,
+    },
+    handlers::{tag::dto::TagUsage, TagService},
+    search::SearchIndex,
     traits::sync_cache::SyncCache,
-    utils::db_helper::{RedisActorHelper, RedisCmd, RespValueRedisHelper},
+    utils::{
+        self,
+        db_helper::{RedisActorHelper, RedisCmd, RespValueRedisHelper},
+        entity,
+    },
 };
 
 use super::storage;
@@ -39,12 +55,13 @@ pub async fn add(
     post_data: &AddPostDTO,
     client: &PGClient,
     redis_addr: &Addr<RedisActor>,
-) -> Result<BigInt, MyError> {
+) -> Result<(BigInt, Vec<Mention>), MyError> {
     let _stmt = include_str!("../../../sql/post/add.sql");
     let stmt = client.prepare(&_stmt).await?;
     let post_id = get_next_id()?;
+    let mentions = resolve_mentions(&post_data.content, client).await?;
     let result = client
-        .query(&stmt, &[&post_id, &user.id, &post_data.content])
+        .query(&stmt, &[&post_id, &user.id, &post_data.content, &Json(&mentions)])
         .await?
         .iter()
         .map(|row| row.get("id"))
@@ -54,9 +71,48 @@ pub async fn add(
 
     if let Ok(id) = result {
         save_post_sender_cache(redis_addr, &id, &user.id);
+        // post 已经保存，标签出错不影响结果
+        if let Err(e) = TagService::save_post_tags(&id, &post_data.content, client, redis_addr).await {
+            info!("save post tags error: {}", e);
+        }
+        // 推送到关注者的时间线
+        if let Err(e) = fanout::fanout(&user.id, &id, client, redis_addr).await {
+            info!("fanout error: {}", e);
+        }
     }
 
-    result
+    result.map(|id| (id, mentions))
+}
+
+/// 找出内容中 @ 的用户，不存在的昵称忽略
+/// @ 后面没有空格时，取最长的存在的昵称（比如 @张三你好 中的 张三）
+async fn resolve_mentions(content: &str, client: &PGClient) -> Result<Vec<Mention>, MyError> {
+    let entities = entity::extract_truncated(content, '@', NICK_MAX_LEN);
+    if entities.is_empty() {
+        return Ok(vec![]);
+    }
+    let nicks: Vec<String> = entities.iter().flat_map(|e| e.prefixes()).collect();
+    let _stmt = include_str!("../../../sql/post/get_mentioned_users.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let users: HashMap<String, i32> = client
+        .query(&stmt, &[&nicks])
+        .await?
+        .iter()
+        .map(|row| (row.get("nick"), row.get("id")))
+        .collect();
+    Ok(entities
+        .into_iter()
+        .filter_map(|e| {
+            let nick = e.prefixes().into_iter().find(|nick| users.contains_key(nick))?;
+            let e = e.truncated(nick.chars().count());
+            Some(Mention {
+                user_id: users[&nick],
+                nick,
+                start: e.start,
+                length: e.length,
+            })
+        })
+        .collect())
 }
 
 /// 设置 postId -> userId 映射
@@ -74,6 +130,58 @@ pub fn save_post_sender_cache(redis_addr: &Addr<RedisActor>, post_id: &BigInt, u
     ]);
 }
 
+/// 编辑，原来的内容保存为历史版本
+/// 同时返回新增的 @ 用户
+/// 201 -> 没有权限编辑
+pub async fn edit(
+    user: &UserInfo,
+    data: &EditPostDTO,
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<(EditPostResultDTO, Vec<Mention>), MyError> {
+    let _stmt = include_str!("../../../sql/post/edit.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let mentions = resolve_mentions(&data.content, client).await?;
+    let row = client
+        .query(&stmt, &[&data.id, &user.id, &data.content, &Json(&mentions)])
+        .await?
+        .pop()
+        .ok_or(MyError::err_code(201))?;
+    // post 已经保存，标签出错不影响结果
+    if let Err(e) = TagService::save_post_tags(&data.id, &data.content, client, redis_addr).await {
+        info!("save post tags error: {}", e);
+    }
+
+    let old_mentions = row
+        .try_get::<_, Json<Vec<Mention>>>("old_mentions")
+        .map(|json| json.0)
+        .unwrap_or_default();
+    let added = mentions
+        .iter()
+        .filter(|m| old_mentions.iter().all(|old| old.user_id != m.user_id))
+        .cloned()
+        .collect();
+    let result = EditPostResultDTO {
+        id: row.get("id"),
+        edited_at: row.get("edited_at"),
+        revision_count: row.get("revisions"),
+        mentions,
+    };
+    Ok((result, added))
+}
+
+/// 获取 post 的历史版本
+pub async fn get_revisions(post_id: &i64, client: &PGClient) -> Result<Vec<PostRevision>, MyError> {
+    let _stmt = include_str!("../../../sql/post/get_revisions.sql");
+    let stmt = client.prepare(_stmt).await?;
+    Ok(client
+        .query(&stmt, &[post_id])
+        .await?
+        .iter()
+        .map(PostRevision::from)
+        .collect())
+}
+
 /// 删除推文
 /// 201 -> 没有权限删除
 pub async fn delete(
@@ -88,7 +196,17 @@ pub async fn delete(
     let vec = client.query(&stmt, &[&del_data.id, &user.id]).await?;
 
     // 返回条数 大于0 删除成功
-    if vec.len() > 0 {
+    if !vec.is_empty() {
+        let tags = vec
+            .iter()
+            .filter_map(|row| {
+                Some(TagUsage {
+                    tag: row.get::<_, Option<String>>("tag")?,
+                    hour: row.get::<_, Option<i64>>("hour")?,
+                })
+            })
+            .collect::<Vec<TagUsage>>();
+        TagService::remove_post_tags(&tags, redis_addr);
         // 删除post的redis缓存数据
         let id = &del_data.id;
         redis_addr.del(&RedisKey::post_likes(id)); // 删除赞集合
@@ -122,7 +240,7 @@ pub async fn get_one(
     info!("post_ext: {:?}", post_ext);
 
     // 同步
-    let _ = post_ext.sync_cache_data(Some(user), redis_addr).await;
+    let _ = post_ext.sync_cache_data(Some(user), client, redis_addr).await;
 
     // 获取评论
     let _stmt = include_str!("../../../sql/post/get_comments.sql");
@@ -130,7 +248,7 @@ pub async fn get_one(
     let _skip = PostExtendsWithComment::max_comments() as i64;
     let _offset: i64 = 0;
     let mut comments = client
-        .query(&stmt, &[&post_ext.id, &_skip, &_offset])
+        .query(&stmt, &[&post_ext.id, &_skip, &_offset, CommentSort::Newest.to_i16()])
         .await?
         .iter()
         .map(|row| {
@@ -143,7 +261,7 @@ pub async fn get_one(
     let _result = try_join_all(
         comments
             .iter_mut()
-            .map(|comment| comment.sync_cache_data(Some(user), redis_addr)),
+            .map(|comment| comment.sync_cache_data(Some(user), client, redis_addr)),
     )
     .await;
 
@@ -154,59 +272,93 @@ pub async fn get_one(
     Ok(data)
 }
 
+/// 获取 post 和它下面所有层级的回复
+pub async fn get_thread<'a>(
+    user: &UserInfo,
+    post_id: &i64,
+    paging: &Paging<'a>,
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<PostThread, MyError> {
+    let _stmt = include_str!("../../../sql/post/get.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let mut post = client
+        .query(&stmt, &[post_id])
+        .await?
+        .iter()
+        .map(PostExtends::from)
+        .collect::<Vec<PostExtends>>()
+        .pop()
+        .ok_or(MyError::NotFound)?;
+
+    // 整个对话按 root_id 取出，根 post 的 root_id 是自己
+    let root_id = post.root_id.map_or(*post.id.inner(), |id| *id.inner());
+    let _stmt = include_str!("../../../sql/post/get_thread.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let mut replies = client
+        .query(&stmt, &[post_id, &paging.fetch_limit(), paging.offset(), paging.after(), &root_id])
+        .await?
+        .iter()
+        .map(|row| ThreadReply { post: PostExtends::from(row), depth: row.get("depth") })
+        .collect::<Vec<ThreadReply>>();
+    let next = paging.take_next(&mut replies);
+
+    let _ = post.sync_cache_data(Some(user), client, redis_addr).await;
+    let _result = try_join_all(
+        replies
+            .iter_mut()
+            .map(|reply| reply.post.sync_cache_data(Some(user), client, redis_addr)),
+    )
+    .await;
+    Ok(PostThread::new(post, replies, next))
+}
+
+/// 获取评论（翻页）
+pub async fn get_comments<'a>(
+    user: &UserInfo,
+    post_id: &BigInt,
+    sort: &CommentSort,
+    paging: &Paging<'a>,
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<Vec<PostExtends>, MyError> {
+    let _stmt = include_str!("../../../sql/post/get_comments.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let vec = client
+        .query(
+            &stmt,
+            &[post_id, &paging.fetch_limit(), paging.offset(), sort.to_i16()],
+        )
+        .await?;
+
+    Ok(join_all(vec.iter().map(|row| async move {
+        let mut comment = PostExtends::from(row);
+        let _ = comment.sync_cache_data(Some(user), client, redis_addr).await;
+        comment
+    }))
+    .await)
+}
+
 /// 点赞
+/// 201 -> 状态没有改变
 pub async fn like(
     post_id: &i64,
     user_id: &i32,
+    client: &PGClient,
     redis_addr: &Addr<RedisActor>,
-) -> Result<(), MyError> {
-    let likes_key = RedisKey::post_likes(post_id);
-    // 判断是否重复点赞
-    let liked = redis_addr
-        .exec(RedisCmd::sismember(&likes_key, &user_id.to_string()))
-        .await?;
-    if liked.integer_to_bool() {
-        // 已经点赞
-        return Err(MyError::err_code(201));
-    }
-
-    redis_addr
-        .exec_all(vec![
-            // 添加进点赞集合
-            RedisCmd::sadd(&likes_key, &user_id.to_string()),
-            // 增加点赞数
-            RedisCmd::incr(&RedisKey::post_like_count(post_id)),
-        ])
-        .await?;
-    Ok(())
+) -> Result<ReactionResultDTO, MyError> {
+    react(Reaction::Like, true, post_id, user_id, client, redis_addr).await
 }
 
 /// 取消点赞
+/// 201 -> 状态没有改变
 pub async fn cancel_like(
     post_id: &i64,
     user_id: &i32,
+    client: &PGClient,
     redis_addr: &Addr<RedisActor>,
-) -> Result<(), MyError> {
-    let likes_key = RedisKey::post_likes(post_id);
-
-    let liked = redis_addr
-        .exec(RedisCmd::sismember(&likes_key, &user_id.to_string()))
-        .await?;
-
-    if !liked.integer_to_bool() {
-        // 没有点赞，取消点赞则返回
-        return Err(MyError::err_code(201));
-    }
-
-    redis_addr
-        .exec_all(vec![
-            // 移除出点赞集合
-            RedisCmd::srem(&likes_key, &user_id.to_string()),
-            // 减少点赞数
-            RedisCmd::decr(&RedisKey::post_like_count(post_id)),
-        ])
-        .await?;
-    Ok(())
+) -> Result<ReactionResultDTO, MyError> {
+    react(Reaction::Like, false, post_id, user_id, client, redis_addr).await
 }
 
 /// 查看我的post
@@ -216,16 +368,30 @@ pub async fn get_mine<'a>(
     client: &PGClient,
     redis_addr: &Addr<RedisActor>,
 ) -> Result<Vec<PostExtends>, MyError> {
-    let _stmt = include_str!("../../../sql/post/get_list.sql");
+    get_list(user, &user.id, paging, client, redis_addr).await
+}
+
+/// 某个用户的 post
+pub async fn get_list<'a>(
+    user: &UserInfo,
+    sender_id: &i32,
+    paging: &Paging<'a>,
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<Vec<PostExtends>, MyError> {
+    let (_stmt, cursor) = paging.cursor_query(
+        include_str!("../../../sql/post/get_list.sql"),
+        include_str!("../../../sql/post/get_list_after.sql"),
+    );
     let stmt = client.prepare(_stmt).await?;
     let vec = client
-        .query(&stmt, &[&user.id, paging.limit(), paging.offset()])
+        .query(&stmt, &[sender_id, &paging.fetch_limit(), paging.offset(), cursor])
         .await?;
 
     Ok(join_all(vec.iter().map(|row| async move {
         // move 把row引用带出闭包
         let mut post = PostExtends::from(row);
-        let _ = post.sync_cache_data(Some(user), redis_addr).await;
+        let _ = post.sync_cache_data(Some(user), client, redis_addr).await;
         post
     }))
     .await)
@@ -236,92 +402,195 @@ pub async fn comment(
     user: &UserInfo,
     data: &CommentPostDTO,
     client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
 ) -> Result<CommentResult, MyError> {
     let _stmt = include_str!("../../../sql/post/comment.sql");
     let stmt = client.prepare(&_stmt).await?;
     let post_id = get_next_id()?;
+    let mentions = resolve_mentions(&data.content, client).await?;
 
-    client
-        .query(&stmt, &[&post_id, &user.id, &data.content, &data.origin_id])
+    let mut result = client
+        .query(&stmt, &[&post_id, &user.id, &data.content, &data.origin_id, &Json(&mentions)])
         .await?
         .iter()
         .map(|row| CommentResult::from(row))
         .collect::<Vec<CommentResult>>()
         .pop()
-        .ok_or(MyError::NotFound)
+        .ok_or(MyError::NotFound)?;
+    result.mentions = mentions;
+    // post 已经保存，标签出错不影响结果
+    if let Err(e) = TagService::save_post_tags(&post_id, &data.content, client, redis_addr).await {
+        info!("save post tags error: {}", e);
+    }
+
+    // 评论也会出现在时间线上
+    if let Err(e) = fanout::fanout(&user.id, &post_id, client, redis_addr).await {
+        info!("fanout error: {}", e);
+    }
+    Ok(result)
 }
 
 /// 反感
+/// 201 -> 状态没有改变
 pub async fn hate(
     post_id: &i64,
     user_id: &i32,
+    client: &PGClient,
     redis_addr: &Addr<RedisActor>,
-) -> Result<(), MyError> {
-    let hate_key = RedisKey::post_hates(post_id);
-    // 判断是否重复不喜欢
-    let hated = redis_addr
-        .exec(RedisCmd::sismember(&hate_key, &user_id.to_string()))
-        .await?;
-    if hated.integer_to_bool() {
-        // 已经不喜欢
-        return Err(MyError::err_code(201));
-    }
-
-    redis_addr
-        .exec_all(vec![
-            // 添加进不喜欢集合
-            RedisCmd::sadd(&hate_key, &user_id.to_string()),
-            // 增加讨厌数
-            RedisCmd::incr(&RedisKey::post_hate_count(post_id)),
-        ])
-        .await?;
-    Ok(())
+) -> Result<ReactionResultDTO, MyError> {
+    react(Reaction::Hate, true, post_id, user_id, client, redis_addr).await
 }
 
 /// 取消反感
+/// 201 -> 状态没有改变
 pub async fn cancel_hate(
     post_id: &i64,
     user_id: &i32,
+    client: &PGClient,
     redis_addr: &Addr<RedisActor>,
-) -> Result<(), MyError> {
-    let hate_key = RedisKey::post_hates(post_id);
+) -> Result<ReactionResultDTO, MyError> {
+    react(Reaction::Hate, false, post_id, user_id, client, redis_addr).await
+}
 
-    let hated = redis_addr
-        .exec(RedisCmd::sismember(&hate_key, &user_id.to_string()))
+/// 浏览
+pub async fn browse<'a>(
+    user: &UserInfo,
+    client: &PGClient,
+    paging: &Paging<'a>,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<Vec<PostExtends>, MyError> {
+    let (_stmt, cursor) = paging.cursor_query(
+        include_str!("../../../sql/post/browse.sql"),
+        include_str!("../../../sql/post/browse_after.sql"),
+    );
+    let stmt = client.prepare(_stmt).await?;
+    let vec = client
+        .query(&stmt, &[&paging.fetch_limit(), paging.offset(), cursor])
         .await?;
 
-    if !hated.integer_to_bool() {
-        // 没有反感，返回
-        return Err(MyError::err_code(201));
-    }
-
-    redis_addr
-        .exec_all(vec![
-            // 移除出反感集合
-            RedisCmd::srem(&hate_key, &user_id.to_string()),
-            // 减少反感数
-            RedisCmd::decr(&RedisKey::post_hate_count(post_id)),
-        ])
-        .await?;
-    Ok(())
+    Ok(join_all(vec.iter().map(|row| async move {
+        let mut post = PostExtends::from(row);
+        let _ = post.sync_cache_data(Some(user), client, redis_addr).await;
+        post
+    }))
+    .await)
 }
 
-/// 浏览
-pub async fn browse<'a>(
+/// 全文搜索，按相关度排序
+pub async fn search<'a>(
     user: &UserInfo,
+    data: &SearchPostDTO,
     client: &PGClient,
     paging: &Paging<'a>,
     redis_addr: &Addr<RedisActor>,
 ) -> Result<Vec<PostExtends>, MyError> {
-    let _stmt = include_str!("../../../sql/post/browse.sql");
+    let _stmt = include_str!("../../../sql/post/search.sql");
     let stmt = client.prepare(_stmt).await?;
     let vec = client
-        .query(&stmt, &[paging.limit(), paging.offset()])
+        .query(
+            &stmt,
+            &[
+                &paging.fetch_limit(),
+                paging.offset(),
+                &data.keyword,
+                &data.sender_id,
+                &data.since,
+                &data.until,
+                &data.is_comment,
+            ],
+        )
         .await?;
 
     Ok(join_all(vec.iter().map(|row| async move {
         let mut post = PostExtends::from(row);
-        let _ = post.sync_cache_data(Some(user), redis_addr).await;
+        post.snippet = post.snippet.as_deref().map(headline_to_html);
+        let _ = post.sync_cache_data(Some(user), client, redis_addr).await;
+        post
+    }))
+    .await)
+}
+
+/// ts_headline 用 \u{2} \u{3} 标记匹配的词，转义后换成 <em>
+/// 和进程内索引返回的片段格式一致
+fn headline_to_html(headline: &str) -> String {
+    utils::escape_html(headline)
+        .replace('\u{2}', "<em>")
+        .replace('\u{3}', "</em>")
+}
+
+/// 用进程内的索引搜索，再从数据库获取 post
+pub async fn search_indexed<'a>(
+    index: &SearchIndex,
+    user: &UserInfo,
+    data: &SearchPostDTO,
+    client: &PGClient,
+    paging: &Paging<'a>,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<Vec<PostExtends>, MyError> {
+    let hits = index.search_posts(data, *paging.offset() as usize, paging.fetch_limit() as usize)?;
+    let ids: Vec<i64> = hits.iter().map(|(id, _)| *id).collect();
+    let _stmt = include_str!("../../../sql/post/get_by_ids.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let mut posts: HashMap<i64, PostExtends> = client
+        .query(&stmt, &[&ids])
+        .await?
+        .iter()
+        .map(|row| (row.get("id"), PostExtends::from(row)))
+        .collect();
+    // 按相关度的顺序，索引还没同步的已删除 post 跳过
+    let list: Vec<PostExtends> = hits
+        .into_iter()
+        .filter_map(|(id, snippet)| {
+            posts.remove(&id).map(|mut post| {
+                post.snippet = snippet;
+                post
+            })
+        })
+        .collect();
+
+    Ok(join_all(list.into_iter().map(|mut post| async move {
+        let _ = post.sync_cache_data(Some(user), client, redis_addr).await;
+        post
+    }))
+    .await)
+}
+
+/// 我关注的人和我自己的 post
+pub async fn timeline<'a>(
+    user: &UserInfo,
+    client: &PGClient,
+    paging: &Paging<'a>,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<Vec<PostExtends>, MyError> {
+    let vec = match fanout::get_timeline_ids(&user.id, paging, client, redis_addr).await? {
+        Some(ids) => {
+            let _stmt = include_str!("../../../sql/post/get_by_ids.sql");
+            let stmt = client.prepare(_stmt).await?;
+            let mut rows: HashMap<i64, Row> = client
+                .query(&stmt, &[&ids])
+                .await?
+                .into_iter()
+                .map(|row| (row.get("id"), row))
+                .collect();
+            // 按时间线上的顺序
+            ids.iter().filter_map(|id| rows.remove(id)).collect()
+        }
+        None => {
+            // 超出缓存的部分直接查库
+            let (_stmt, cursor) = paging.cursor_query(
+                include_str!("../../../sql/post/timeline.sql"),
+                include_str!("../../../sql/post/timeline_after.sql"),
+            );
+            let stmt = client.prepare(_stmt).await?;
+            client
+                .query(&stmt, &[&user.id, &paging.fetch_limit(), paging.offset(), cursor])
+                .await?
+        }
+    };
+
+    Ok(join_all(vec.iter().map(|row| async move {
+        let mut post = PostExtends::from(row);
+        let _ = post.sync_cache_data(Some(user), client, redis_addr).await;
         post
     }))
     .await)
@@ -357,4 +626,251 @@ pub async fn get_post_sender_from_id(
         return Ok(user_id);
     }
     Err(MyError::NotFound)
-}
\ No newline at end of file
+}
+
+/// 原子地改变点赞/反感状态，点赞会取消反感，反之亦然
+/// 变更同时记录到 redis 上，等待落库
+async fn react(
+    reaction: Reaction,
+    added: bool,
+    post_id: &i64,
+    user_id: &i32,
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<ReactionResultDTO, MyError> {
+    // 已删除的 post 不能点赞/反感
+    let _stmt = include_str!("../../../sql/post/is_active.sql");
+    let stmt = client.prepare(_stmt).await?;
+    client.query_opt(&stmt, &[post_id]).await?.ok_or(MyError::NotFound)?;
+    ensure_reaction_cache(post_id, client, redis_addr).await?;
+
+    let opposite = reaction.opposite();
+    let (set_key, count_key) = reaction.cache_keys(post_id);
+    let (opposite_set_key, opposite_count_key) = opposite.cache_keys(post_id);
+    let result = redis_addr
+        .exec(RedisCmd::eval(
+            include_str!("../../../lua/post/react.lua"),
+            vec![
+                set_key,
+                count_key,
+                opposite_set_key,
+                opposite_count_key,
+                RedisKey::reaction_changes(),
+            ],
+            vec![
+                user_id.to_string(),
+                (added as u8).to_string(),
+                ReactionChange::new(reaction, post_id, user_id, added).to_string(),
+                ReactionChange::new(opposite, post_id, user_id, false).to_string(),
+            ],
+        ))
+        .await?;
+    let result = Vec::<i64>::from_resp(result).map_err(|_| MyError::ParseError)?;
+    if result.len() != 5 {
+        return Err(MyError::ParseError);
+    }
+    if result[0] == 0 {
+        return Err(MyError::err_code(201));
+    }
+    let (count, opposite_count) = (result[1] as i32, result[2] as i32);
+    let (state, opposite_state) = (result[3] == 1, result[4] == 1);
+    Ok(match reaction {
+        Reaction::Like => ReactionResultDTO {
+            like_count: count,
+            hate_count: opposite_count,
+            liked: state,
+            hated: opposite_state,
+        },
+        Reaction::Hate => ReactionResultDTO {
+            like_count: opposite_count,
+            hate_count: count,
+            liked: opposite_state,
+            hated: state,
+        },
+    })
+}
+
+/// 把 redis 上记录的点赞/反感变更写入 postgres
+/// 先把变更列表重命名成一个批次，保证取出和新写入互不影响
+/// post 或用户已经不存在的变更会被跳过，只有数据库出错时才把这一批放回列表头部，等待下次重试
+pub async fn flush_reaction_changes(
+    client: &mut PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<usize, MyError> {
+    let batch_key = RedisKey::reaction_changes_batch(Uuid::new_v4());
+    let renamed = redis_addr
+        .exec(RedisCmd::rename(RedisKey::reaction_changes(), &batch_key))
+        .await?;
+    if let RespValue::Error(_) = renamed {
+        // 没有需要落库的变更
+        return Ok(0);
+    }
+
+    let entries = Vec::<String>::from_resp(
+        redis_addr
+            .exec(RedisCmd::lrange(&batch_key, "0", "-1"))
+            .await?,
+    )
+    .map_err(|_| MyError::ParseError)?;
+
+    // 同一个用户对同一个 post 的多次变更，只保留最后一次
+    let mut latest = HashMap::new();
+    for entry in &entries {
+        match entry.parse::<ReactionChange>() {
+            Ok(change) => {
+                latest.insert((change.reaction, change.post_id, change.user_id), change);
+            }
+            Err(_) => info!("invalid reaction change: {}", entry),
+        }
+    }
+
+    let result = save_reaction_changes(client, latest.values()).await;
+    if result.is_err() {
+        let mut entries = entries;
+        entries.reverse();
+        redis_addr.do_send(RedisCmd::lpush_all(RedisKey::reaction_changes(), entries));
+    }
+    redis_addr.del(&batch_key);
+    result.map(|_| latest.len())
+}
+
+/// 在一个事务里写入点赞/反感变更
+async fn save_reaction_changes(
+    client: &mut PGClient,
+    changes: impl Iterator<Item = &ReactionChange>,
+) -> Result<(), MyError> {
+    let transaction = client.transaction().await?;
+    let like = transaction.prepare(include_str!("../../../sql/post/like.sql")).await?;
+    let unlike = transaction.prepare(include_str!("../../../sql/post/unlike.sql")).await?;
+    let hate = transaction.prepare(include_str!("../../../sql/post/hate.sql")).await?;
+    let unhate = transaction.prepare(include_str!("../../../sql/post/unhate.sql")).await?;
+    for change in changes {
+        let stmt = match (change.reaction, change.added) {
+            (Reaction::Like, true) => &like,
+            (Reaction::Like, false) => &unlike,
+            (Reaction::Hate, true) => &hate,
+            (Reaction::Hate, false) => &unhate,
+        };
+        transaction
+            .execute(stmt, &[&change.post_id, &change.user_id])
+            .await?;
+    }
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// 确保 post 的点赞/反感缓存存在，不存在则从 postgres 重建
+pub async fn ensure_reaction_cache(
+    post_id: &i64,
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<(), MyError> {
+    let cached = redis_addr
+        .exec_all(vec![
+            RedisCmd::exists(RedisKey::post_like_count(post_id)),
+            RedisCmd::exists(RedisKey::post_hate_count(post_id)),
+        ])
+        .await?
+        .iter()
+        .all(|val| val.integer_to_bool());
+    if cached {
+        return Ok(());
+    }
+    load_reaction_cache(post_id, client, redis_addr).await
+}
+
+/// 从 postgres 重建 post 的点赞/反感缓存
+/// redis 上已有的数量不会被覆盖
+pub async fn load_reaction_cache(
+    post_id: &i64,
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<(), MyError> {
+    let script = include_str!("../../../lua/post/load_reactions.lua");
+    let likes = get_reaction_users(include_str!("../../../sql/post/get_likes.sql"), post_id, client).await?;
+    let hates = get_reaction_users(include_str!("../../../sql/post/get_hates.sql"), post_id, client).await?;
+    redis_addr
+        .exec_all(vec![
+            RedisCmd::eval(
+                script,
+                vec![RedisKey::post_likes(post_id), RedisKey::post_like_count(post_id)],
+                likes,
+            ),
+            RedisCmd::eval(
+                script,
+                vec![RedisKey::post_hates(post_id), RedisKey::post_hate_count(post_id)],
+                hates,
+            ),
+        ])
+        .await?;
+    Ok(())
+}
+
+/// 把 redis 集合里的点赞/反感导入 postgres
+/// 只在 redis 上有记录的旧数据需要导入，已不存在的 post 和用户会被跳过
+pub async fn import_reactions(
+    reaction: Reaction,
+    post_id: &i64,
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<u64, MyError> {
+    let (set_key, _) = reaction.cache_keys(post_id);
+    let users = Vec::<String>::from_resp(redis_addr.exec(RedisCmd::smembers(set_key)).await?)
+        .map_err(|_| MyError::ParseError)?
+        .iter()
+        .filter_map(|id| id.parse::<i32>().ok())
+        .collect::<Vec<i32>>();
+    if users.is_empty() {
+        return Ok(0);
+    }
+    let _stmt = match reaction {
+        Reaction::Like => include_str!("../../../sql/post/import_likes.sql"),
+        Reaction::Hate => include_str!("../../../sql/post/import_hates.sql"),
+    };
+    let stmt = client.prepare(_stmt).await?;
+    Ok(client.execute(&stmt, &[post_id, &users]).await?)
+}
+
+/// 获取点赞/反感过的用户id
+async fn get_reaction_users(
+    query: &str,
+    post_id: &i64,
+    client: &PGClient,
+) -> Result<Vec<String>, MyError> {
+    let stmt = client.prepare(query).await?;
+    Ok(client
+        .query(&stmt, &[post_id])
+        .await?
+        .iter()
+        .map(|row| row.get::<_, i32>("user_id").to_string())
+        .collect())
+}
+
+/// 获取最近几天的 post id
+pub async fn get_recent_ids(days: &i32, client: &PGClient) -> Result<Vec<i64>, MyError> {
+    let _stmt = include_str!("../../../sql/post/get_recent_ids.sql");
+    let stmt = client.prepare(_stmt).await?;
+    Ok(client
+        .query(&stmt, &[days])
+        .await?
+        .iter()
+        .map(|row| row.get("id"))
+        .collect())
+}
+
+/// 彻底删除超过保留天数的已删除 post
+/// 还有回复的不删除，等回复都删除后再删除
+pub async fn purge_deleted(days: &i32, client: &PGClient) -> Result<u64, MyError> {
+    let _stmt = include_str!("../../../sql/post/purge_deleted.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let mut total = 0;
+    loop {
+        // 每次删除没有回复的，删除后它的父级可能也没有回复了
+        let count = client.execute(&stmt, &[days]).await?;
+        if count == 0 {
+            break;
+        }
+        total += count;
+    }
+    Ok(total)
+}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct GetTrendingDTO {
+    /// 统计最近几个小时，默认 24
+    pub hours: Option<i64>,
+    /// 数量，默认 10
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct TrendingTag {
+    pub tag: String,
+    /// 使用次数
+    pub count: i64,
+}
+
+/// 计入热门统计的一次标签使用
+pub struct TagUsage {
+    pub tag: String,
+    /// 计入的小时
+    pub hour: i64,
+}
@@ -0,0 +1,50 @@
+use actix::Addr;
+use actix_redis::RedisActor;
+use actix_web::{web, HttpResponse, Responder};
+
+use crate::{
+    base::{
+        paging_data::{GetCursorPageDTO, Paging},
+        pg_client::PGClient,
+        user_info::UserInfo,
+    },
+    errors::MyError,
+};
+
+use super::{
+    dto::GetTrendingDTO,
+    service::TAG_MAX_LEN,
+    storage::{self, TRENDING_MAX_HOURS},
+};
+
+/// 某个标签下的 post（翻页）
+pub async fn get_tag_posts(
+    user: UserInfo,
+    name: web::Path<String>,
+    body: web::Query<GetCursorPageDTO>,
+    client: PGClient,
+    redis_addr: web::Data<Addr<RedisActor>>,
+) -> Result<impl Responder, actix_web::Error> {
+    let tag = name.trim_start_matches('#').to_lowercase();
+    if tag.is_empty() || tag.chars().count() > TAG_MAX_LEN {
+        return Err(MyError::FailResultError.into());
+    }
+    let paging = Paging::cursor(&body.page, &body.page_size, &body.before, &body.after)?;
+    let list = storage::get_tag_posts(&user, &tag, &client, &paging, &redis_addr).await?;
+    paging.finish_with_cursor(list)
+}
+
+/// 热门标签
+pub async fn trending(
+    _user: UserInfo,
+    body: web::Query<GetTrendingDTO>,
+    redis_addr: web::Data<Addr<RedisActor>>,
+) -> Result<impl Responder, actix_web::Error> {
+    let hours = body.hours.unwrap_or(24);
+    let limit = body.limit.unwrap_or(10);
+    if !(1..=TRENDING_MAX_HOURS).contains(&hours) || !(1..=50).contains(&limit) {
+        return Err(MyError::FailResultError.into());
+    }
+    let list = storage::get_trending(&hours, &limit, &redis_addr).await?;
+    Ok(HttpResponse::Ok().json(list))
+}
@@ -0,0 +1,4 @@
+pub mod handler;
+pub mod dto;
+pub mod service;
+mod storage;
@@ -0,0 +1,69 @@
+use actix::Addr;
+use actix_redis::RedisActor;
+
+use crate::{base::pg_client::PGClient, errors::MyError, utils::entity};
+
+use super::{dto::TagUsage, storage};
+
+/// 标签最大长度
+pub const TAG_MAX_LEN: usize = 32;
+
+/// 提取内容中的 #标签，统一小写并去重
+pub fn parse_tags(content: &str) -> Vec<String> {
+    let mut tags: Vec<String> = vec![];
+    for e in entity::extract(content, '#', TAG_MAX_LEN) {
+        let tag = e.text.to_lowercase();
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// 保存 post 的标签（替换原来的），新增的标签计入热门统计，移除的从统计中减掉
+pub async fn save_post_tags(
+    post_id: &i64,
+    content: &str,
+    client: &PGClient,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<(), MyError> {
+    let tags = parse_tags(content);
+    let (added, removed) = storage::save_post_tags(post_id, &tags, client).await?;
+    storage::incr_usage(&added, redis_addr);
+    storage::decr_usage(&removed, redis_addr);
+    Ok(())
+}
+
+/// 删除 post 后，把它的标签从热门统计中减掉
+pub fn remove_post_tags(tags: &[TagUsage], redis_addr: &Addr<RedisActor>) {
+    storage::decr_usage(tags, redis_addr);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_tags_lowercase_dedupe() {
+        assert_eq!(parse_tags("#Rust #rust #RUST #go"), vec!["rust", "go"]);
+    }
+
+    #[test]
+    fn parse_tags_max_len() {
+        let max = "a".repeat(TAG_MAX_LEN);
+        let long = "a".repeat(TAG_MAX_LEN + 1);
+        assert_eq!(parse_tags(&format!("#{} #{}", max, long)), vec![max]);
+    }
+
+    #[test]
+    fn parse_tags_cjk() {
+        assert_eq!(parse_tags("今天#天气#好，#Rust编程"), vec!["天气", "好", "rust编程"]);
+    }
+
+    #[test]
+    fn parse_tags_skip_word_prefix() {
+        // 英文单词后面的 # 不算，比如 issue#1、C#
+        assert_eq!(parse_tags("see issue#1 and C# a@b.c"), Vec::<String>::new());
+        assert_eq!(parse_tags("# ##"), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,129 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use actix::Addr;
+use actix_redis::RedisActor;
+use futures::future::join_all;
+use redis_async::resp::FromResp;
+
+use crate::{
+    base::{paging_data::Paging, pg_client::PGClient, redis_key::RedisKey, user_info::UserInfo},
+    data_models::post::PostExtends,
+    errors::MyError,
+    traits::sync_cache::SyncCache,
+    utils::db_helper::{RedisActorHelper, RedisCmd},
+};
+
+use super::dto::{TagUsage, TrendingTag};
+
+/// 热门标签最多统计最近几个小时
+pub const TRENDING_MAX_HOURS: i64 = 7 * 24;
+/// 热门标签的合并结果缓存时间（秒）
+const TRENDING_CACHE_SECS: i64 = 60;
+
+/// 当前是第几个小时
+fn current_hour() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64 / 3600)
+        .unwrap_or_default()
+}
+
+/// 保存 post 的标签，不在 tags 中的会被移除
+/// 返回新增的和移除的标签
+pub async fn save_post_tags(
+    post_id: &i64,
+    tags: &[String],
+    client: &PGClient,
+) -> Result<(Vec<TagUsage>, Vec<TagUsage>), MyError> {
+    let _stmt = include_str!("../../../sql/tag/save_post_tags.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let (mut added, mut removed) = (vec![], vec![]);
+    for row in client.query(&stmt, &[post_id, &tags]).await? {
+        let usage = TagUsage { tag: row.get("tag"), hour: row.get("hour") };
+        if row.get("added") {
+            added.push(usage);
+        } else {
+            removed.push(usage);
+        }
+    }
+    Ok((added, removed))
+}
+
+/// 标签使用次数计入使用时的小时
+pub fn incr_usage(tags: &[TagUsage], redis_addr: &Addr<RedisActor>) {
+    add_usage(tags, "1", redis_addr);
+}
+
+/// 从使用时的小时里减掉标签使用次数
+pub fn decr_usage(tags: &[TagUsage], redis_addr: &Addr<RedisActor>) {
+    add_usage(tags, "-1", redis_addr);
+}
+
+fn add_usage(tags: &[TagUsage], increment: &str, redis_addr: &Addr<RedisActor>) {
+    // 超出统计范围的小时已经过期，不用再改
+    let oldest = current_hour() - TRENDING_MAX_HOURS;
+    let mut cmds = vec![];
+    for usage in tags.iter().filter(|usage| usage.hour > oldest) {
+        let key = RedisKey::tag_usage(usage.hour);
+        cmds.push(RedisCmd::zincrby(&key, increment, &usage.tag));
+        // 超过统计范围后过期
+        cmds.push(RedisCmd::expireat(&key, ((usage.hour + TRENDING_MAX_HOURS + 1) * 3600).to_string()));
+    }
+    if !cmds.is_empty() {
+        redis_addr.do_send_all(cmds);
+    }
+}
+
+/// 最近几个小时使用最多的标签
+pub async fn get_trending(
+    hours: &i64,
+    limit: &i64,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<Vec<TrendingTag>, MyError> {
+    let hour = current_hour();
+    let mut keys = vec![RedisKey::tag_trending(hours)];
+    keys.extend((0..*hours).map(|i| RedisKey::tag_usage(hour - i)));
+    let result = redis_addr
+        .exec(RedisCmd::eval(
+            include_str!("../../../lua/tag/trending.lua"),
+            keys,
+            vec![TRENDING_CACHE_SECS.to_string(), limit.to_string()],
+        ))
+        .await?;
+    let list = Vec::<String>::from_resp(result).map_err(|_| MyError::ParseError)?;
+    Ok(list
+        .chunks(2)
+        .filter_map(|pair| match pair {
+            [tag, count] => count.parse::<f64>().ok().map(|count| TrendingTag {
+                tag: tag.clone(),
+                count: count as i64,
+            }),
+            _ => None,
+        })
+        .collect())
+}
+
+/// 某个标签下的 post
+pub async fn get_tag_posts<'a>(
+    user: &UserInfo,
+    tag: &str,
+    client: &PGClient,
+    paging: &Paging<'a>,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<Vec<PostExtends>, MyError> {
+    let (_stmt, cursor) = paging.cursor_query(
+        include_str!("../../../sql/tag/get_tag_posts.sql"),
+        include_str!("../../../sql/tag/get_tag_posts_after.sql"),
+    );
+    let stmt = client.prepare(_stmt).await?;
+    let vec = client
+        .query(&stmt, &[&paging.fetch_limit(), paging.offset(), cursor, &tag])
+        .await?;
+
+    Ok(join_all(vec.iter().map(|row| async move {
+        let mut post = PostExtends::from(row);
+        let _ = post.sync_cache_data(Some(user), client, redis_addr).await;
+        post
+    }))
+    .await)
+}
@@ -1,6 +1,13 @@
-use serde::{Deserialize, Serialize};
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
 
-use crate::data_models::user::UserData;
+use crate::{
+    data_models::user::{
+        UserData, UserProfile, BIO_MAX_LEN, DISPLAY_NAME_MAX_LEN, LOCATION_MAX_LEN, NICK_MAX_LEN,
+        WEBSITE_MAX_LEN,
+    },
+    errors::MyError,
+};
 
 #[derive(Deserialize, Serialize)]
 pub struct RegisterUserDTO {
@@ -34,10 +41,248 @@ pub struct ChangeNickDTO {
     pub nick: String,
 }
 
+#[derive(Serialize)]
+pub struct ChangeNickResultDTO {
+    pub nick: String,
+    /// 新的 token，旧的 token 里还是原来的昵称
+    pub token: String,
+}
+
+/// 修改资料，不传的字段不修改
+#[derive(Deserialize)]
+pub struct UpdateProfileDTO {
+    pub nick: Option<String>,
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    pub location: Option<String>,
+    pub website: Option<String>,
+    /// 传 null 时清空
+    #[serde(default, deserialize_with = "nullable")]
+    pub birthday: Option<Option<NaiveDate>>,
+}
+
+/// 区分没有传和传了 null
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// 昵称只能包含字母、数字、_ 和 -，这样才能被 @
+/// 301 -> 昵称不合法
+pub fn validate_nick(nick: &str) -> Result<(), MyError> {
+    let len = nick.chars().count();
+    let valid = len > 0
+        && len <= NICK_MAX_LEN
+        && nick.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(MyError::err_code(301))
+    }
+}
+
+/// 只支持 http/https，必须有域名，不能有空白字符
+fn is_valid_url(url: &str) -> bool {
+    let rest = match url.strip_prefix("https://").or_else(|| url.strip_prefix("http://")) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+    host.contains('.')
+        && !host.starts_with('.')
+        && !host.ends_with('.')
+        && !url.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+impl UpdateProfileDTO {
+    /// 去掉前后空白
+    pub fn trimmed(self) -> Self {
+        let trim = |s: Option<String>| s.map(|s| s.trim().to_string());
+        Self {
+            nick: trim(self.nick),
+            display_name: trim(self.display_name),
+            bio: trim(self.bio),
+            location: trim(self.location),
+            website: trim(self.website),
+            birthday: self.birthday,
+        }
+    }
+
+    /// 301 -> 昵称不合法
+    /// 302 -> 显示名称太长
+    /// 303 -> 个人简介太长
+    /// 304 -> 所在地太长
+    /// 305 -> 网址不合法
+    /// 306 -> 生日不合法
+    pub fn validate(&self) -> Result<(), MyError> {
+        let too_long = |s: &Option<String>, max: usize| {
+            s.as_ref().is_some_and(|s| s.chars().count() > max)
+        };
+        if let Some(nick) = &self.nick {
+            validate_nick(nick)?;
+        }
+        if too_long(&self.display_name, DISPLAY_NAME_MAX_LEN) {
+            return Err(MyError::err_code(302));
+        }
+        if too_long(&self.bio, BIO_MAX_LEN) {
+            return Err(MyError::err_code(303));
+        }
+        if too_long(&self.location, LOCATION_MAX_LEN) {
+            return Err(MyError::err_code(304));
+        }
+        // 空字符串表示清空
+        let invalid_website = self.website.as_ref().is_some_and(|url| {
+            !url.is_empty() && (url.len() > WEBSITE_MAX_LEN || !is_valid_url(url))
+        });
+        if invalid_website {
+            return Err(MyError::err_code(305));
+        }
+        if let Some(Some(birthday)) = &self.birthday {
+            let earliest = NaiveDate::from_ymd_opt(1900, 1, 1);
+            if Some(*birthday) < earliest || *birthday > Utc::now().naive_utc().date() {
+                return Err(MyError::err_code(306));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+pub struct UpdateProfileResultDTO {
+    pub profile: UserProfile,
+    /// 修改了昵称时返回新的 token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
 
 
 #[derive(Deserialize, Serialize)]
 pub struct SearchUserDTO {
     pub nick: String,
     pub page: i64,
-}
\ No newline at end of file
+    /// 每页数量
+    pub page_size: Option<i64>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct AutocompleteUserDTO {
+    /// 输入的昵称
+    pub q: String,
+    /// 数量，默认 10
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct UserSuggestionDTO {
+    #[serde(flatten)]
+    pub user: UserData,
+    /// 我关注了对方
+    pub following: bool,
+    /// 对方关注了我
+    pub followed_by: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn code(result: Result<(), MyError>) -> Option<i32> {
+        match result {
+            Ok(()) => None,
+            Err(MyError::OkError(code)) => Some(code),
+            Err(e) => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    fn profile(json: &str) -> UpdateProfileDTO {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn nick_length() {
+        assert_eq!(code(validate_nick("")), Some(301));
+        assert_eq!(code(validate_nick("a")), None);
+        assert_eq!(code(validate_nick(&"a".repeat(NICK_MAX_LEN))), None);
+        assert_eq!(code(validate_nick(&"a".repeat(NICK_MAX_LEN + 1))), Some(301));
+    }
+
+    #[test]
+    fn nick_chars() {
+        // 按字符数计算长度
+        assert_eq!(code(validate_nick(&"张".repeat(NICK_MAX_LEN))), None);
+        assert_eq!(code(validate_nick(&"张".repeat(NICK_MAX_LEN + 1))), Some(301));
+        assert_eq!(code(validate_nick("tom_1-2")), None);
+        assert_eq!(code(validate_nick("tom cat")), Some(301));
+        assert_eq!(code(validate_nick("tom@cat")), Some(301));
+        assert_eq!(code(validate_nick("#tom")), Some(301));
+    }
+
+    #[test]
+    fn url() {
+        assert!(is_valid_url("http://a.b"));
+        assert!(is_valid_url("https://example.com/path?q=1#top"));
+        assert!(!is_valid_url("https://.x"));
+        assert!(!is_valid_url("https://x."));
+        assert!(!is_valid_url("https://localhost"));
+        assert!(!is_valid_url("ftp://a.b"));
+        assert!(!is_valid_url("a.b"));
+        assert!(!is_valid_url("https://a.b/x y"));
+        assert!(!is_valid_url("https://a.b\n"));
+    }
+
+    #[test]
+    fn profile_lengths() {
+        let max = "张".repeat(DISPLAY_NAME_MAX_LEN);
+        let json = format!(r#"{{"display_name": "{}"}}"#, max);
+        assert_eq!(code(profile(&json).validate()), None);
+        let json = format!(r#"{{"display_name": "{}张"}}"#, max);
+        assert_eq!(code(profile(&json).validate()), Some(302));
+        let json = format!(r#"{{"bio": "{}"}}"#, "a".repeat(BIO_MAX_LEN + 1));
+        assert_eq!(code(profile(&json).validate()), Some(303));
+        let json = format!(r#"{{"location": "{}"}}"#, "a".repeat(LOCATION_MAX_LEN + 1));
+        assert_eq!(code(profile(&json).validate()), Some(304));
+        assert_eq!(code(profile(r#"{"nick": "a b"}"#).validate()), Some(301));
+    }
+
+    #[test]
+    fn profile_website() {
+        // 空字符串表示清空
+        assert_eq!(code(profile(r#"{"website": ""}"#).validate()), None);
+        assert_eq!(code(profile(r#"{"website": "https://a.b"}"#).validate()), None);
+        assert_eq!(code(profile(r#"{"website": "https://.x"}"#).validate()), Some(305));
+        // 前后的空白 trimmed 之后才合法
+        let dto = profile(r#"{"website": " https://a.b "}"#);
+        assert_eq!(code(dto.validate()), Some(305));
+        assert_eq!(code(profile(r#"{"website": " https://a.b "}"#).trimmed().validate()), None);
+        let long = format!("https://a.b/{}", "a".repeat(WEBSITE_MAX_LEN));
+        let json = format!(r#"{{"website": "{}"}}"#, long);
+        assert_eq!(code(profile(&json).validate()), Some(305));
+    }
+
+    #[test]
+    fn profile_birthday() {
+        assert_eq!(code(profile(r#"{"birthday": "1900-01-01"}"#).validate()), None);
+        assert_eq!(code(profile(r#"{"birthday": "1899-12-31"}"#).validate()), Some(306));
+        let tomorrow = Utc::now().naive_utc().date() + Duration::days(1);
+        let json = format!(r#"{{"birthday": "{}"}}"#, tomorrow);
+        assert_eq!(code(profile(&json).validate()), Some(306));
+    }
+
+    #[test]
+    fn birthday_nullable() {
+        // 没有传 -> 不修改，传 null -> 清空
+        assert_eq!(profile("{}").birthday, None);
+        assert_eq!(profile(r#"{"birthday": null}"#).birthday, Some(None));
+        assert_eq!(
+            profile(r#"{"birthday": "2000-02-29"}"#).birthday,
+            Some(NaiveDate::from_ymd_opt(2000, 2, 29))
+        );
+        assert_eq!(code(profile(r#"{"birthday": null}"#).validate()), None);
+    }
+}
@@ -1,8 +1,17 @@
 use crate::{
-    base::{user_info::UserInfo, pg_client::PGClient, paging_data::Paging}, errors::MyError,
+    base::{
+        user_info::UserInfo,
+        pg_client::PGClient,
+        paging_data::{GetCursorPageDTO, Paging},
+    },
+    errors::MyError,
     handlers::user::{auth as AuthHandler, storage}, handlers::user::dto::*,
+    handlers::PostService,
+    search::{self, SearchIndex},
 };
 
+use actix::Addr;
+use actix_redis::RedisActor;
 use actix_web::{web, Error, HttpResponse};
 use log::info;
 
@@ -12,10 +21,9 @@ pub async fn register(
     user_info: web::Json<RegisterUserDTO>,
     client: PGClient,
 ) -> Result<HttpResponse, MyError> {
-    if user_info.nick.is_empty() {
-        return Err(MyError::err_code(301));
-    }
+    validate_nick(&user_info.nick)?;
     let new_user = storage::add(&client, user_info.0).await?;
+    search::sync_user(&new_user.id, &client).await;
     info!("creating a new user:{}", new_user.nick);
     let result = RegisterResultDTO {
         id: new_user.id,
@@ -74,10 +82,32 @@ pub async fn change_nick(
     data: web::Json<ChangeNickDTO>,
     client: PGClient,
 ) -> Result<HttpResponse, MyError> {
-    let mut data = data.into_inner();
+    validate_nick(&data.nick)?;
     let nick = storage::change_nick(&client, &user.id, &data.nick).await?;
-    data.nick = nick;
-    Ok(HttpResponse::Ok().json(data))
+    search::sync_user(&user.id, &client).await;
+    // token 里有昵称，需要重新签发
+    let token = AuthHandler::create_jwt(&user.id, &nick)?;
+    Ok(HttpResponse::Ok().json(ChangeNickResultDTO { nick, token }))
+}
+
+/// 修改资料
+pub async fn update_profile(
+    user: UserInfo,
+    data: web::Json<UpdateProfileDTO>,
+    client: PGClient,
+) -> Result<HttpResponse, MyError> {
+    let data = data.into_inner().trimmed();
+    data.validate()?;
+    let (nick, nick_changed) = storage::update_profile(&client, &user.id, &data).await?;
+    let token = if nick_changed {
+        search::sync_user(&user.id, &client).await;
+        // token 里有昵称，需要重新签发
+        Some(AuthHandler::create_jwt(&user.id, &nick)?)
+    } else {
+        None
+    };
+    let profile = storage::get_profile(&client, &user.id, &user.id).await?;
+    Ok(HttpResponse::Ok().json(UpdateProfileResultDTO { profile, token }))
 }
 
 /// 搜索用户
@@ -85,7 +115,52 @@ pub async fn search_user(
     body: web::Json<SearchUserDTO>,
     client: PGClient,
 ) -> Result<HttpResponse, actix_web::Error> {
-    let paging = Paging::default(&body.page)?;
+    let paging = Paging::sized(&body.page, &body.page_size)?;
+    if let Some(index) = SearchIndex::get() {
+        let (list, total) = storage::search_user_indexed(index, &client, &body.nick, &paging).await?;
+        return paging.finish_with_total(list, total);
+    }
     let list = storage::search_user(&client, &body.nick, &paging).await?;
-    paging.finish(list)
+    let total = storage::search_user_count(&client, &body.nick).await?;
+    paging.finish_with_total(list, total)
+}
+
+/// 输入时的用户提示
+pub async fn autocomplete(
+    user: UserInfo,
+    query: web::Query<AutocompleteUserDTO>,
+    client: PGClient,
+) -> Result<HttpResponse, MyError> {
+    /// 最多返回的数量
+    const MAX_LIMIT: i64 = 20;
+    let keyword = query.q.trim();
+    if keyword.is_empty() {
+        return Ok(HttpResponse::Ok().json(Vec::<UserSuggestionDTO>::new()));
+    }
+    let limit = query.limit.unwrap_or(10).clamp(1, MAX_LIMIT);
+    let list = storage::autocomplete(&client, &user.id, keyword, &limit).await?;
+    Ok(HttpResponse::Ok().json(list))
+}
+
+/// 用户主页
+pub async fn profile(
+    user: UserInfo,
+    id: web::Path<i32>,
+    client: PGClient,
+) -> Result<HttpResponse, MyError> {
+    let profile = storage::get_profile(&client, &id, &user.id).await?;
+    Ok(HttpResponse::Ok().json(profile))
+}
+
+/// 某个用户的 posts（翻页）
+pub async fn posts(
+    user: UserInfo,
+    id: web::Path<i32>,
+    body: web::Query<GetCursorPageDTO>,
+    client: PGClient,
+    redis_addr: web::Data<Addr<RedisActor>>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let paging = Paging::cursor(&body.page, &body.page_size, &body.before, &body.after)?;
+    let list = PostService::get_list(&user, &id, &paging, &client, &redis_addr).await?;
+    paging.finish_with_cursor(list)
 }
@@ -1,12 +1,22 @@
 use crate::{
-    data_models::user::{User, UserData},
+    data_models::user::{User, UserData, UserProfile},
     errors::MyError,
-    handlers::user::dto::{LoginUserDTO, RegisterUserDTO},
+    handlers::user::dto::{LoginUserDTO, RegisterUserDTO, UpdateProfileDTO, UserSuggestionDTO},
     utils, base::{paging_data::Paging, pg_client::PGClient},
+    search::SearchIndex,
 };
 use log::info;
 use tokio_postgres::error::SqlState;
 
+/// 名字重复 -> 201
+fn map_nick_conflict(e: tokio_postgres::Error) -> MyError {
+    info!("{}", e);
+    match e.code() {
+        Some(&SqlState::UNIQUE_VIOLATION) => MyError::err_code(201),
+        _ => MyError::PGError(e),
+    }
+}
+
 /// 数据库添加用户
 pub async fn add(client: &PGClient, mut user_info: RegisterUserDTO) -> Result<UserData, MyError> {
     let _stmt = include_str!("../../../sql/user/add_user.sql");
@@ -20,18 +30,7 @@ pub async fn add(client: &PGClient, mut user_info: RegisterUserDTO) -> Result<Us
     client
         .query(&stmt, &[&user_info.nick, &user_info.pwd, &_salt])
         .await
-        .map_err(|e| {
-            info!("{}", e);
-            if let Some(state) = e.code() {
-                match state {
-                    // 名字重复
-                    &SqlState::UNIQUE_VIOLATION => MyError::err_code(201),
-                    _ => MyError::PGError(e),
-                }
-            } else {
-                MyError::PGError(e)
-            }
-        })? // 注册失败
+        .map_err(map_nick_conflict)? // 注册失败
         .iter()
         .map(|row| UserData::new(&row.get("id"), &row.get("nick"), None))
         .collect::<Vec<UserData>>()
@@ -96,8 +95,9 @@ pub async fn change_nick(client: &PGClient, id: &i32, nick: &String) -> Result<S
     let _stmt = include_str!("../../../sql/user/change_nick.sql");
     let stmt = client.prepare(_stmt).await?;
     client
-        .query(&stmt, &[id, nick])
-        .await?
+        .query(&stmt, &[nick, id])
+        .await
+        .map_err(map_nick_conflict)?
         .iter()
         .map(|r| r.get("nick"))
         .collect::<Vec<String>>()
@@ -105,14 +105,142 @@ pub async fn change_nick(client: &PGClient, id: &i32, nick: &String) -> Result<S
         .ok_or(MyError::FailResultError)
 }
 
-/// 搜索用户
+/// 转义 LIKE 中的通配符
+fn like_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// 搜索用户，完全匹配和前缀匹配的排在前面
 pub async fn search_user<'a>(client: &PGClient, nick: &String, paging: &Paging<'a>) -> Result<Vec<UserData>, MyError> {
     let _stmt = include_str!("../../../sql/user/search_user.sql");
     let stmt = client.prepare(_stmt).await?;
+    let escaped = like_escape(nick);
     Ok(client
-        .query(&stmt, &[&format!("%{}%", nick), paging.limit(), paging.offset()])
+        .query(
+            &stmt,
+            &[
+                &format!("%{}%", escaped),
+                &paging.fetch_limit(),
+                paging.offset(),
+                nick,
+                &format!("{}%", escaped),
+            ],
+        )
         .await?
         .iter()
         .map(|row| UserData::from(row))
         .collect::<Vec<UserData>>())
-}
\ No newline at end of file
+}
+
+/// 搜索用户的总数
+pub async fn search_user_count(client: &PGClient, nick: &str) -> Result<i64, MyError> {
+    let _stmt = include_str!("../../../sql/user/search_user_count.sql");
+    let stmt = client.prepare(_stmt).await?;
+    Ok(client
+        .query_one(&stmt, &[&format!("%{}%", like_escape(nick))])
+        .await?
+        .get("total"))
+}
+/// 少于这个长度的关键字只做前缀匹配
+/// 三元组至少要 3 个字符，更短的模糊匹配用不上索引，会扫全表
+const AUTOCOMPLETE_FUZZY_MIN_LEN: usize = 3;
+
+/// 输入时的用户提示
+/// 完全匹配 > 前缀匹配 > 其他，同一级里互相关注和我关注的人排在前面
+pub async fn autocomplete(
+    client: &PGClient,
+    user_id: &i32,
+    keyword: &str,
+    limit: &i64,
+) -> Result<Vec<UserSuggestionDTO>, MyError> {
+    let rows = if keyword.chars().count() < AUTOCOMPLETE_FUZZY_MIN_LEN {
+        let _stmt = include_str!("../../../sql/user/autocomplete_prefix.sql");
+        let stmt = client.prepare(_stmt).await?;
+        let prefix = format!("{}%", like_escape(&keyword.to_lowercase()));
+        client.query(&stmt, &[&keyword, user_id, &prefix, limit]).await?
+    } else {
+        let _stmt = include_str!("../../../sql/user/autocomplete.sql");
+        let stmt = client.prepare(_stmt).await?;
+        let escaped = like_escape(keyword);
+        client
+            .query(
+                &stmt,
+                &[&keyword, user_id, &format!("%{}%", escaped), &format!("{}%", escaped), limit],
+            )
+            .await?
+    };
+    Ok(rows
+        .iter()
+        .map(|row| UserSuggestionDTO {
+            user: UserData::from(row),
+            following: row.get("following"),
+            followed_by: row.get("followed_by"),
+        })
+        .collect())
+}
+
+/// 用户主页
+pub async fn get_profile(client: &PGClient, id: &i32, viewer_id: &i32) -> Result<UserProfile, MyError> {
+    let _stmt = include_str!("../../../sql/user/get_profile.sql");
+    let stmt = client.prepare(_stmt).await?;
+    client
+        .query_opt(&stmt, &[id, viewer_id])
+        .await?
+        .map(|row| UserProfile::from(&row))
+        .ok_or(MyError::NotFound)
+}
+
+/// 修改资料，返回修改后的昵称和昵称是否改变
+/// 201 -> 昵称重复
+pub async fn update_profile(
+    client: &PGClient,
+    id: &i32,
+    data: &UpdateProfileDTO,
+) -> Result<(String, bool), MyError> {
+    let _stmt = include_str!("../../../sql/user/update_profile.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let row = client
+        .query_opt(
+            &stmt,
+            &[
+                id,
+                &data.nick,
+                &data.display_name,
+                &data.bio,
+                &data.location,
+                &data.website,
+                &data.birthday.is_some(),
+                &data.birthday.flatten(),
+            ],
+        )
+        .await
+        .map_err(map_nick_conflict)?
+        .ok_or(MyError::NotFound)?;
+    let nick: String = row.get("nick");
+    let changed = nick != row.get::<_, String>("old_nick");
+    Ok((nick, changed))
+}
+
+/// 用进程内的索引搜索用户，返回当前页和总数
+pub async fn search_user_indexed<'a>(
+    index: &SearchIndex,
+    client: &PGClient,
+    nick: &str,
+    paging: &Paging<'a>,
+) -> Result<(Vec<UserData>, i64), MyError> {
+    let (ids, total) =
+        index.search_users(nick, *paging.offset() as usize, paging.fetch_limit() as usize)?;
+    let _stmt = include_str!("../../../sql/user/get_by_ids.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let rows = client.query(&stmt, &[&ids]).await?;
+    // 按相关度的顺序
+    let list = ids
+        .iter()
+        .filter_map(|id| {
+            rows.iter()
+                .find(|row| row.get::<_, i32>("id") == *id)
+                .map(UserData::from)
+        })
+        .collect();
+    Ok((list, total))
+}
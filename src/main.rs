@@ -3,19 +3,25 @@ mod config;
 mod data_models;
 mod errors;
 mod handlers;
+mod search;
 mod utils;
+mod tasks;
 mod traits;
 mod wrap;
 
 use std::time::Duration;
 
+use crate::base::paging_data::Paging;
 use crate::config::WepoConfig;
+use crate::handlers::msg::push::NoticeHub;
+use crate::search::SearchIndex;
 use crate::wrap::delay::DevDelay;
 use crate::{
     handlers::UserHandler,
     handlers::PostHandler,
     handlers::MsgHandler,
     handlers::FriendshipHandler,
+    handlers::TagHandler,
 };
 use actix_web::middleware;
 use ::config::Config;
@@ -38,8 +44,52 @@ async fn main() -> std::io::Result<()> {
         .unwrap();
 
     let config: WepoConfig = config_.try_deserialize().unwrap();
+    Paging::init_max_page_size(config.max_page_size);
+    NoticeHub::init(&config.redis_addr);
     let redis_addr = RedisActor::start(config.redis_addr.clone());
     let pool = config.pg.create_pool(None, NoTls).unwrap();
+    let task_pool = pool.clone();
+    let task_redis_addr = redis_addr.clone();
+
+    // wepo warm-cache: 只重建缓存，不启动服务器
+    if std::env::args().nth(1).as_deref() == Some("warm-cache") {
+        return tasks::warm_cache::run(&task_pool, &task_redis_addr, &config.warm_cache_days)
+            .await
+            .map_err(std::io::Error::other);
+    }
+
+    // wepo purge-posts: 彻底删除过期的已删除 post，不启动服务器
+    if std::env::args().nth(1).as_deref() == Some("purge-posts") {
+        return tasks::purge_posts::run(&task_pool, &config.deleted_post_retention_days)
+            .await
+            .map_err(std::io::Error::other);
+    }
+
+    // wepo reindex: 重建搜索索引，不启动服务器
+    if std::env::args().nth(1).as_deref() == Some("reindex") {
+        return tasks::reindex::run(&task_pool, &config.search_index_dir)
+            .await
+            .map_err(std::io::Error::other);
+    }
+
+    // wepo migrate-reactions: 把只存在 redis 上的点赞/反感导入 postgres，不启动服务器
+    if std::env::args().nth(1).as_deref() == Some("migrate-reactions") {
+        return tasks::migrate_reactions::run(&task_pool, &task_redis_addr)
+            .await
+            .map_err(std::io::Error::other);
+    }
+
+    // 搜索索引会锁住目录，只在服务器里打开
+    if let Some(dir) = &config.search_index_dir {
+        SearchIndex::init(dir).map_err(std::io::Error::other)?;
+    }
+
+    // 点赞/反感定时落库
+    tasks::reaction_flusher::start(
+        task_pool.clone(),
+        task_redis_addr.clone(),
+        Duration::from_secs(config.reaction_flush_secs),
+    );
 
     let server = HttpServer::new(move || {
         let cors = Cors::default()
@@ -66,11 +116,17 @@ async fn main() -> std::io::Result<()> {
                             .route("/login", post().to(UserHandler::login)) // 账号密码登录
                             .route("/token_refresh", get().to(UserHandler::login_with_token)) // token 登录
                             .route("/change_nick", post().to(UserHandler::change_nick)) // 修改昵称
+                            .route("/update_profile", post().to(UserHandler::update_profile)) // 修改资料
                             .route("/search_user", post().to(UserHandler::search_user)) // 查找用户
+                            .route("/autocomplete", get().to(UserHandler::autocomplete)) // 输入时的用户提示
+                            .route("/{id}", get().to(UserHandler::profile)) // 用户主页
+                            .route("/{id}/posts", get().to(UserHandler::posts)) // 某个用户的 post（翻页）
                     )
                     .service(
                         web::scope("/post")
                             .route("/send", post().to(PostHandler::add)) // 发送
+                            .route("/edit", post().to(PostHandler::edit)) // 编辑
+                            .route("/revisions", get().to(PostHandler::revisions)) // 历史版本
                             .route("/delete", delete().to(PostHandler::delete)) // 删除
                             .route("/like", get().to(PostHandler::like)) // 喜欢
                             .route("/cancel_like", get().to(PostHandler::cancel_like)) // 取消喜欢
@@ -79,22 +135,46 @@ async fn main() -> std::io::Result<()> {
                             .route("/get_post", get().to(PostHandler::get_one)) // 获取某个
                             .route("/my_post", post().to(PostHandler::mine)) // 获取我的（翻页）
                             .route("/comment", post().to(PostHandler::comment)) // 评论
+                            .route("/comments", get().to(PostHandler::comments)) // 获取评论（翻页）
+                            .route("/thread", get().to(PostHandler::thread)) // 获取所有层级的回复（翻页）
                             .route("/browse", get().to(PostHandler::browse)) // 浏览所有（翻页）
+                            .route("/timeline", get().to(PostHandler::timeline)) // 浏览关注的人（翻页）
+                            .route("/search", get().to(PostHandler::search)) // 搜索（翻页）
                     )
                     .service(
                         web::scope("/msg")
                         .route("/unread", get().to(MsgHandler::get_unread_msg)) // 获取未读消息数量
+                        .route("/read", post().to(MsgHandler::read)) // 标记已读
+                        .route("/read_all", get().to(MsgHandler::read_all)) // 全部标记已读
+                        .route("/ws", get().to(MsgHandler::ws)) // 通知推送
+                        .route("/settings", get().to(MsgHandler::get_settings)) // 获取通知设置
+                        .route("/set_type", post().to(MsgHandler::set_notice_type)) // 开启/关闭某个类型的通知
+                        .route("/mute_user", post().to(MsgHandler::mute_user)) // 屏蔽用户
+                        .route("/unmute_user", post().to(MsgHandler::unmute_user)) // 取消屏蔽用户
+                        .route("/mute_post", post().to(MsgHandler::mute_post)) // 屏蔽 post
+                        .route("/unmute_post", post().to(MsgHandler::unmute_post)) // 取消屏蔽 post
+                        .route("/all", post().to(MsgHandler::get_all_notices)) // 获取所有类型的通知
                         .route("/comments", post().to(MsgHandler::get_comment_notices)) // 获取评论通知
                         .route("/likes", post().to(MsgHandler::get_like_notices)) // 获取点赞通知
                         .route("/hates", post().to(MsgHandler::get_hate_notices)) // 获取反感通知
+                        .route("/likes_grouped", post().to(MsgHandler::get_like_notice_groups)) // 获取聚合的点赞通知
+                        .route("/hates_grouped", post().to(MsgHandler::get_hate_notice_groups)) // 获取聚合的反感通知
+                        .route("/group_notices", post().to(MsgHandler::get_group_notices)) // 获取聚合通知中的单条通知
                         .route("/friend_add", post().to(MsgHandler::get_add_friend_notices)) // 好友添加通知
                         .route("/friend_remove", post().to(MsgHandler::get_remove_friend_notices)) // 好友移除通知
+                        .route("/mentions", post().to(MsgHandler::get_mention_notices)) // 获取 @ 通知
                     )
+                    .route("/stream", get().to(MsgHandler::stream)) // 通知和新 post 的事件流
                     .service(
                         web::scope("/friend")
                         .route("add", post().to(FriendshipHandler::add_friendship)) // 添加好友
                         .route("remove", post().to(FriendshipHandler::remove_friendship)) // 移除好友
                     )
+                    .service(
+                        web::scope("/tag")
+                        .route("/trending", get().to(TagHandler::trending)) // 热门标签
+                        .route("/{name}", get().to(TagHandler::get_tag_posts)) // 某个标签下的 post（翻页）
+                    )
             )
     })
     .bind(config.server_addr.clone())?
@@ -102,7 +182,13 @@ async fn main() -> std::io::Result<()> {
 
     info!("Server running at http://{}/", config.server_addr);
 
-    server.await
+    server.await?;
+
+    // 关闭前把剩下的变更落库
+    if let Err(e) = tasks::reaction_flusher::flush(&task_pool, &task_redis_addr).await {
+        log::error!("flush reaction changes error: {}", e);
+    }
+    Ok(())
 }
 
 /// 初始化日志
@@ -0,0 +1,423 @@
+mod tokenizer;
+
+use std::{
+    fs,
+    ops::Bound,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        RwLock,
+    },
+    thread,
+    time::Duration,
+};
+
+use chrono::NaiveDateTime;
+use log::{error, info};
+use once_cell::sync::OnceCell;
+use tantivy::{
+    collector::{Count, TopDocs},
+    directory::MmapDirectory,
+    doc,
+    query::{BooleanQuery, Occur, Query, QueryParser, RangeQuery, TermQuery},
+    schema::{
+        Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, Value, FAST, INDEXED,
+        STORED,
+    },
+    snippet::SnippetGenerator,
+    tokenizer::{LowerCaser, NgramTokenizer, TextAnalyzer},
+    Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, TantivyError, Term,
+};
+use tokio_postgres::Row;
+
+use crate::{base::pg_client::PGClient, errors::MyError, handlers::PostDTO::SearchPostDTO};
+
+use self::tokenizer::JiebaTokenizer;
+
+/// 配置了索引目录时才会启用
+static SEARCH_INDEX: OnceCell<SearchIndex> = OnceCell::new();
+
+/// 提交间隔，提交后才能搜索到
+const COMMIT_INTERVAL: Duration = Duration::from_secs(1);
+/// 每个索引写入时使用的内存
+const WRITER_MEMORY: usize = 20_000_000;
+/// 重建索引时每批读取的数量
+const REINDEX_BATCH: i64 = 1000;
+/// 搜索结果中片段的最大长度
+const SNIPPET_MAX_CHARS: usize = 100;
+
+/// 中文分词
+const JIEBA_TOKENIZER: &str = "jieba";
+/// 昵称按 1~3 个字切分，方便搜索昵称的一部分
+const NICK_TOKENIZER: &str = "nick_ngram";
+
+/// 一个 tantivy 索引，写入后由后台线程定时提交
+struct IndexPart {
+    index: Index,
+    reader: IndexReader,
+    writer: RwLock<IndexWriter>,
+    dirty: AtomicBool,
+}
+
+impl IndexPart {
+    fn open(dir: &Path, schema: Schema) -> Result<Self, MyError> {
+        fs::create_dir_all(dir).map_err(TantivyError::from)?;
+        let directory = MmapDirectory::open(dir).map_err(TantivyError::from)?;
+        let index = Index::open_or_create(directory, schema)?;
+        index.tokenizers().register(
+            JIEBA_TOKENIZER,
+            TextAnalyzer::builder(JiebaTokenizer).filter(LowerCaser).build(),
+        );
+        index.tokenizers().register(
+            NICK_TOKENIZER,
+            TextAnalyzer::builder(NgramTokenizer::new(1, 3, false)?)
+                .filter(LowerCaser)
+                .build(),
+        );
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()?;
+        let writer = index.writer_with_num_threads(1, WRITER_MEMORY)?;
+        Ok(Self { index, reader, writer: RwLock::new(writer), dirty: AtomicBool::new(false) })
+    }
+
+    fn add(&self, id_term: Term, doc: TantivyDocument) -> Result<(), MyError> {
+        let writer = self.writer.read().map_err(|_| MyError::PoisonError)?;
+        writer.delete_term(id_term);
+        writer.add_document(doc)?;
+        self.dirty.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn delete(&self, id_term: Term) -> Result<(), MyError> {
+        let writer = self.writer.read().map_err(|_| MyError::PoisonError)?;
+        writer.delete_term(id_term);
+        self.dirty.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<(), MyError> {
+        let writer = self.writer.read().map_err(|_| MyError::PoisonError)?;
+        writer.delete_all_documents()?;
+        self.dirty.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// 有变更时提交
+    fn commit(&self) -> Result<(), MyError> {
+        if self.dirty.swap(false, Ordering::Relaxed) {
+            let mut writer = self.writer.write().map_err(|_| MyError::PoisonError)?;
+            writer.commit()?;
+        }
+        Ok(())
+    }
+}
+
+struct PostFields {
+    id: Field,
+    sender: Field,
+    content: Field,
+    create_time: Field,
+    is_comment: Field,
+}
+
+struct UserFields {
+    id: Field,
+    nick: Field,
+}
+
+/// 进程内的搜索索引，post 用中文分词，用户昵称用 n-gram
+pub struct SearchIndex {
+    posts: IndexPart,
+    post_fields: PostFields,
+    users: IndexPart,
+    user_fields: UserFields,
+}
+
+/// 写入索引的 post
+pub struct PostDoc {
+    pub id: i64,
+    pub sender: i32,
+    pub content: String,
+    pub create_time: NaiveDateTime,
+    pub is_comment: bool,
+}
+
+impl From<&Row> for PostDoc {
+    fn from(row: &Row) -> Self {
+        Self {
+            id: row.get("id"),
+            sender: row.get("sender"),
+            content: row.get("content"),
+            create_time: row.get("create_time"),
+            is_comment: row.get::<_, Option<i64>>("extends").is_some(),
+        }
+    }
+}
+
+fn text_options(tokenizer: &str) -> TextOptions {
+    TextOptions::default().set_indexing_options(
+        TextFieldIndexing::default()
+            .set_tokenizer(tokenizer)
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+    )
+}
+
+impl SearchIndex {
+    /// 打开（不存在时创建）索引，并启动定时提交
+    pub fn init(dir: &str) -> Result<(), MyError> {
+        let index = Self::open(dir)?;
+        if SEARCH_INDEX.set(index).is_ok() {
+            thread::spawn(|| loop {
+                thread::sleep(COMMIT_INTERVAL);
+                if let Some(index) = Self::get() {
+                    if let Err(e) = index.commit() {
+                        error!("commit search index error: {}", e);
+                    }
+                }
+            });
+            info!("search index opened at {}", dir);
+        }
+        Ok(())
+    }
+
+    /// 没有启用时返回 None
+    pub fn get() -> Option<&'static SearchIndex> {
+        SEARCH_INDEX.get()
+    }
+
+    /// 打开（不存在时创建）索引，会锁住目录，同一时间只能有一个进程打开
+    /// 不会定时提交，需要自己调用 commit
+    pub fn open(dir: &str) -> Result<Self, MyError> {
+        let dir = Path::new(dir);
+        let mut builder = Schema::builder();
+        let post_fields = PostFields {
+            id: builder.add_i64_field("id", INDEXED | STORED | FAST),
+            sender: builder.add_i64_field("sender", INDEXED),
+            content: builder.add_text_field("content", text_options(JIEBA_TOKENIZER) | STORED),
+            create_time: builder.add_i64_field("create_time", INDEXED | FAST),
+            is_comment: builder.add_bool_field("is_comment", INDEXED),
+        };
+        let posts = IndexPart::open(&dir.join("posts"), builder.build())?;
+
+        let mut builder = Schema::builder();
+        let user_fields = UserFields {
+            id: builder.add_i64_field("id", INDEXED | STORED | FAST),
+            nick: builder.add_text_field("nick", text_options(NICK_TOKENIZER)),
+        };
+        let users = IndexPart::open(&dir.join("users"), builder.build())?;
+
+        Ok(Self { posts, post_fields, users, user_fields })
+    }
+
+    /// 添加或更新 post
+    pub fn index_post(&self, post: &PostDoc) -> Result<(), MyError> {
+        let f = &self.post_fields;
+        self.posts.add(
+            Term::from_field_i64(f.id, post.id),
+            doc!(
+                f.id => post.id,
+                f.sender => post.sender as i64,
+                f.content => post.content.as_str(),
+                f.create_time => post.create_time.timestamp(),
+                f.is_comment => post.is_comment,
+            ),
+        )
+    }
+
+    /// 移除 post
+    pub fn delete_post(&self, post_id: &i64) -> Result<(), MyError> {
+        self.posts.delete(Term::from_field_i64(self.post_fields.id, *post_id))
+    }
+
+    /// 添加或更新用户
+    pub fn index_user(&self, user_id: &i32, nick: &str) -> Result<(), MyError> {
+        let f = &self.user_fields;
+        self.users.add(
+            Term::from_field_i64(f.id, *user_id as i64),
+            doc!(f.id => *user_id as i64, f.nick => nick),
+        )
+    }
+
+    /// 清空所有索引
+    fn clear(&self) -> Result<(), MyError> {
+        self.posts.clear()?;
+        self.users.clear()
+    }
+
+    /// 提交所有索引
+    fn commit(&self) -> Result<(), MyError> {
+        self.posts.commit()?;
+        self.users.commit()
+    }
+
+    /// 搜索 post，按相关度排序
+    /// 返回 post id 和高亮的片段
+    pub fn search_posts(
+        &self,
+        data: &SearchPostDTO,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<(i64, Option<String>)>, MyError> {
+        let f = &self.post_fields;
+        let mut parser = QueryParser::for_index(&self.posts.index, vec![f.content]);
+        parser.set_conjunction_by_default();
+        let (keyword, _) = parser.parse_query_lenient(&data.keyword);
+
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![(Occur::Must, keyword)];
+        if let Some(sender_id) = data.sender_id {
+            clauses.push((
+                Occur::Must,
+                Box::new(TermQuery::new(
+                    Term::from_field_i64(f.sender, sender_id as i64),
+                    IndexRecordOption::Basic,
+                )),
+            ));
+        }
+        if data.since.is_some() || data.until.is_some() {
+            clauses.push((
+                Occur::Must,
+                Box::new(RangeQuery::new_i64_bounds(
+                    "create_time".to_string(),
+                    data.since.map_or(Bound::Unbounded, |t| Bound::Included(t.timestamp())),
+                    data.until.map_or(Bound::Unbounded, |t| Bound::Excluded(t.timestamp())),
+                )),
+            ));
+        }
+        if let Some(is_comment) = data.is_comment {
+            clauses.push((
+                Occur::Must,
+                Box::new(TermQuery::new(
+                    Term::from_field_bool(f.is_comment, is_comment),
+                    IndexRecordOption::Basic,
+                )),
+            ));
+        }
+        let query = BooleanQuery::new(clauses);
+
+        let searcher = self.posts.reader.searcher();
+        let mut snippets = SnippetGenerator::create(&searcher, &query, f.content)?;
+        snippets.set_max_num_chars(SNIPPET_MAX_CHARS);
+        searcher
+            .search(&query, &TopDocs::with_limit(limit).and_offset(offset))?
+            .into_iter()
+            .map(|(_, address)| {
+                let doc: TantivyDocument = searcher.doc(address)?;
+                let id = doc
+                    .get_first(f.id)
+                    .and_then(|value| value.as_i64())
+                    .ok_or(MyError::ParseError)?;
+                let mut snippet = snippets.snippet_from_doc(&doc);
+                snippet.set_snippet_prefix_postfix("<em>", "</em>");
+                Ok((id, (!snippet.is_empty()).then(|| snippet.to_html())))
+            })
+            .collect()
+    }
+
+    /// 按昵称搜索用户，返回用户 id 和总数
+    pub fn search_users(
+        &self,
+        nick: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Vec<i32>, i64), MyError> {
+        let f = &self.user_fields;
+        let parser = QueryParser::for_index(&self.users.index, vec![f.nick]);
+        let (query, _) = parser.parse_query_lenient(nick);
+        let searcher = self.users.reader.searcher();
+        let (top, total) = searcher.search(
+            &query,
+            &(TopDocs::with_limit(limit).and_offset(offset), Count),
+        )?;
+        let ids = top
+            .into_iter()
+            .map(|(_, address)| {
+                let doc: TantivyDocument = searcher.doc(address)?;
+                doc.get_first(f.id)
+                    .and_then(|value| value.as_i64())
+                    .map(|id| id as i32)
+                    .ok_or(MyError::ParseError)
+            })
+            .collect::<Result<Vec<i32>, MyError>>()?;
+        Ok((ids, total as i64))
+    }
+}
+
+/// 从数据库同步 post 到索引，已删除的从索引移除
+/// 没有启用索引时不处理
+pub async fn sync_post(post_id: &i64, client: &PGClient) {
+    let Some(index) = SearchIndex::get() else {
+        return;
+    };
+    let result = async {
+        let _stmt = include_str!("../../sql/search/get_post.sql");
+        let stmt = client.prepare(_stmt).await?;
+        match client.query_opt(&stmt, &[post_id]).await? {
+            Some(row) => index.index_post(&PostDoc::from(&row)),
+            None => index.delete_post(post_id),
+        }
+    }
+    .await;
+    if let Err(e) = result {
+        error!("sync post {} to search index error: {}", post_id, e);
+    }
+}
+
+/// 从数据库同步用户到索引
+/// 没有启用索引时不处理
+pub async fn sync_user(user_id: &i32, client: &PGClient) {
+    let Some(index) = SearchIndex::get() else {
+        return;
+    };
+    let result = async {
+        let _stmt = include_str!("../../sql/search/get_user.sql");
+        let stmt = client.prepare(_stmt).await?;
+        let row = client.query_one(&stmt, &[user_id]).await?;
+        index.index_user(user_id, row.get("nick"))
+    }
+    .await;
+    if let Err(e) = result {
+        error!("sync user {} to search index error: {}", user_id, e);
+    }
+}
+
+/// 清空后从数据库重建所有索引
+pub async fn reindex(index: &SearchIndex, client: &PGClient) -> Result<(), MyError> {
+    index.clear()?;
+
+    let _stmt = include_str!("../../sql/search/get_posts.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let (mut last_id, mut count) = (0i64, 0);
+    loop {
+        let rows = client.query(&stmt, &[&last_id, &REINDEX_BATCH]).await?;
+        for row in rows.iter() {
+            let post = PostDoc::from(row);
+            index.index_post(&post)?;
+            last_id = post.id;
+        }
+        count += rows.len();
+        info!("indexed {} posts", count);
+        if (rows.len() as i64) < REINDEX_BATCH {
+            break;
+        }
+    }
+
+    let _stmt = include_str!("../../sql/search/get_users.sql");
+    let stmt = client.prepare(_stmt).await?;
+    let (mut last_id, mut count) = (0i32, 0);
+    loop {
+        let rows = client.query(&stmt, &[&last_id, &REINDEX_BATCH]).await?;
+        for row in rows.iter() {
+            last_id = row.get("id");
+            index.index_user(&last_id, row.get("nick"))?;
+        }
+        count += rows.len();
+        info!("indexed {} users", count);
+        if (rows.len() as i64) < REINDEX_BATCH {
+            break;
+        }
+    }
+
+    index.commit()
+}
@@ -0,0 +1,58 @@
+use jieba_rs::{Jieba, TokenizeMode};
+use once_cell::sync::Lazy;
+use tantivy::tokenizer::{Token, TokenStream, Tokenizer};
+
+/// 结巴分词，加载词典比较慢，只加载一次
+static JIEBA: Lazy<Jieba> = Lazy::new(Jieba::new);
+
+/// 中文分词器，英文和数字按单词切分
+#[derive(Clone)]
+pub struct JiebaTokenizer;
+
+pub struct JiebaTokenStream {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl Tokenizer for JiebaTokenizer {
+    type TokenStream<'a> = JiebaTokenStream;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> JiebaTokenStream {
+        // 结巴返回的是字符位置，tantivy 需要字节位置
+        let mut offsets: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+        offsets.push(text.len());
+        let tokens = JIEBA
+            .tokenize(text, TokenizeMode::Search, true)
+            .into_iter()
+            .filter(|t| t.word.chars().any(|c| c.is_alphanumeric()))
+            .enumerate()
+            .map(|(position, t)| Token {
+                offset_from: offsets[t.start],
+                offset_to: offsets[t.end],
+                position,
+                text: t.word.to_string(),
+                position_length: 1,
+            })
+            .collect();
+        JiebaTokenStream { tokens, index: 0 }
+    }
+}
+
+impl TokenStream for JiebaTokenStream {
+    fn advance(&mut self) -> bool {
+        if self.index < self.tokens.len() {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.tokens[self.index - 1]
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.tokens[self.index - 1]
+    }
+}
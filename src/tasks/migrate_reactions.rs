@@ -0,0 +1,69 @@
+use actix::Addr;
+use actix_redis::{RedisActor, RespValue};
+use deadpool_postgres::Pool;
+use log::{error, info};
+use redis_async::resp::FromResp;
+
+use crate::{
+    base::{pg_client::PGClient, redis_key::RedisKey},
+    errors::MyError,
+    handlers::{post::data::Reaction, PostService},
+    utils::db_helper::{RedisActorHelper, RedisCmd},
+};
+
+/// 每次遍历的键数量
+const SCAN_COUNT: i64 = 1000;
+
+/// 把只存在 redis 上的点赞/反感导入 postgres
+/// 升级后、缓存过期之前执行一次，已导入的不会重复写入
+pub async fn run(pool: &Pool, redis_addr: &Addr<RedisActor>) -> Result<(), MyError> {
+    let client = PGClient::from_pool(pool).await?;
+    for reaction in [Reaction::Like, Reaction::Hate] {
+        let pattern = match reaction {
+            Reaction::Like => RedisKey::post_likes("*"),
+            Reaction::Hate => RedisKey::post_hates("*"),
+        };
+        let prefix = pattern.trim_end_matches('*').to_string();
+        let (mut posts, mut rows) = (0, 0);
+        let mut cursor = String::from("0");
+        loop {
+            let (next, keys) = scan(&cursor, &pattern, redis_addr).await?;
+            for key in keys {
+                let Some(post_id) = key.strip_prefix(&prefix).and_then(|id| id.parse::<i64>().ok()) else {
+                    continue;
+                };
+                match PostService::import_reactions(reaction, &post_id, &client, redis_addr).await {
+                    Ok(count) => {
+                        posts += 1;
+                        rows += count;
+                    }
+                    Err(e) => error!("import {:?} of post {} error: {}", reaction, post_id, e),
+                }
+            }
+            if next == "0" {
+                break;
+            }
+            cursor = next;
+        }
+        info!("imported {:?}: {} posts, {} rows", reaction, posts, rows);
+    }
+    Ok(())
+}
+
+/// 遍历一次，返回下一次的游标和这次的键
+async fn scan(
+    cursor: &str,
+    pattern: &str,
+    redis_addr: &Addr<RedisActor>,
+) -> Result<(String, Vec<String>), MyError> {
+    match redis_addr.exec(RedisCmd::scan(cursor, pattern, SCAN_COUNT)).await? {
+        RespValue::Array(mut result) if result.len() == 2 => {
+            let keys = Vec::<String>::from_resp(result.pop().unwrap_or(RespValue::Nil))
+                .map_err(|_| MyError::ParseError)?;
+            let next = String::from_resp(result.pop().unwrap_or(RespValue::Nil))
+                .map_err(|_| MyError::ParseError)?;
+            Ok((next, keys))
+        }
+        _ => Err(MyError::ParseError),
+    }
+}
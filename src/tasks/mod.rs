@@ -0,0 +1,5 @@
+pub mod reaction_flusher;
+pub mod warm_cache;
+pub mod purge_posts;
+pub mod reindex;
+pub mod migrate_reactions;
@@ -0,0 +1,12 @@
+use deadpool_postgres::Pool;
+use log::info;
+
+use crate::{base::pg_client::PGClient, errors::MyError, handlers::PostService};
+
+/// 彻底删除超过保留天数的已删除 post
+pub async fn run(pool: &Pool, days: &i32) -> Result<(), MyError> {
+    let client = PGClient::from_pool(pool).await?;
+    let count = PostService::purge_deleted(days, &client).await?;
+    info!("purged {} deleted posts", count);
+    Ok(())
+}
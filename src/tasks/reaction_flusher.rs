@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use actix::{clock, spawn, Addr};
+use actix_redis::RedisActor;
+use deadpool_postgres::Pool;
+use log::{error, info};
+
+use crate::{base::pg_client::PGClient, errors::MyError, handlers::PostService};
+
+/// 定时把点赞/反感的变更从 redis 写入 postgres
+pub fn start(pool: Pool, redis_addr: Addr<RedisActor>, period: Duration) {
+    spawn(async move {
+        let mut interval = clock::interval(period);
+        loop {
+            interval.tick().await;
+            if let Err(e) = flush(&pool, &redis_addr).await {
+                error!("flush reaction changes error: {}", e);
+            }
+        }
+    });
+}
+
+/// 立即落库一次（关闭服务器时调用）
+pub async fn flush(pool: &Pool, redis_addr: &Addr<RedisActor>) -> Result<(), MyError> {
+    let mut client = PGClient::from_pool(pool).await?;
+    let count = PostService::flush_reaction_changes(&mut client, redis_addr).await?;
+    if count > 0 {
+        info!("flushed {} reaction changes", count);
+    }
+    Ok(())
+}
@@ -0,0 +1,17 @@
+use deadpool_postgres::Pool;
+use log::info;
+
+use crate::{base::pg_client::PGClient, errors::MyError, search::{self, SearchIndex}};
+
+/// 从数据库重建搜索索引，需要先停止服务器
+pub async fn run(pool: &Pool, dir: &Option<String>) -> Result<(), MyError> {
+    let Some(dir) = dir else {
+        info!("search index is not enabled, set SEARCH_INDEX_DIR first");
+        return Ok(());
+    };
+    let index = SearchIndex::open(dir)?;
+    let client = PGClient::from_pool(pool).await?;
+    search::reindex(&index, &client).await?;
+    info!("search index rebuilt");
+    Ok(())
+}
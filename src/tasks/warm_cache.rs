@@ -0,0 +1,24 @@
+use actix::Addr;
+use actix_redis::RedisActor;
+use deadpool_postgres::Pool;
+use log::{error, info};
+
+use crate::{base::pg_client::PGClient, errors::MyError, handlers::PostService};
+
+/// 重建最近几天 post 的点赞/反感缓存
+/// redis 上已有的数据不会被覆盖，可以在服务运行时执行
+pub async fn run(pool: &Pool, redis_addr: &Addr<RedisActor>, days: &i32) -> Result<(), MyError> {
+    let client = PGClient::from_pool(pool).await?;
+    let ids = PostService::get_recent_ids(days, &client).await?;
+    info!("warming reaction cache of {} posts", ids.len());
+    for (idx, id) in ids.iter().enumerate() {
+        if let Err(e) = PostService::load_reaction_cache(id, &client, redis_addr).await {
+            error!("warm reaction cache of post {} error: {}", id, e);
+        }
+        if (idx + 1) % 1000 == 0 {
+            info!("warmed {}/{}", idx + 1, ids.len());
+        }
+    }
+    info!("reaction cache warmed");
+    Ok(())
+}
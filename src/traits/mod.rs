@@ -1 +1,2 @@
-pub mod sync_cache;
\ No newline at end of file
+pub mod sync_cache;
+pub mod paging_cursor;
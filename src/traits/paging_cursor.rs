@@ -0,0 +1,5 @@
+/// 可以用游标翻页的数据
+pub trait PagingCursor {
+    /// 游标id，按时间递增
+    fn cursor_id(&self) -> i64;
+}
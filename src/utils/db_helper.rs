@@ -72,6 +72,8 @@ impl RedisCmd {
         (set, key, value),
         /// 删除
         (del, key),
+        /// 是否存在
+        (exists, key),
         // ====================================
         /// 自增
         (incr, key),
@@ -88,10 +90,55 @@ impl RedisCmd {
         (sismember, key, member),
         /// 集合移除
         (srem, key, member),
+        /// 集合所有成员
+        (smembers, key),
         /// 数组push
         (lpush, key, value),
+        /// 数组存在时push
+        (lpushx, key, value),
+        /// 数组尾部push
+        (rpush, key, value),
+        /// 数组裁剪
+        (ltrim, key, start, stop),
+        /// 数组范围
+        (lrange, key, start, stop),
+        /// 重命名
+        (rename, key, newkey),
         /// 设置过期
         (expire, key, seconds),
+        /// 设置过期时间点
+        (expireat, key, timestamp),
+        /// 有序集合增加分数
+        (zincrby, key, increment, member),
+    }
+
+    /// 执行 lua 脚本
+    pub fn eval(script: &str, keys: Vec<String>, args: Vec<String>) -> Command {
+        Command(
+            resp_array!["eval", script, keys.len().to_string()]
+                .append(keys)
+                .append(args),
+        )
+    }
+
+    /// 读取 STREAM 中 id 之后的记录
+    pub fn xread(key: impl Into<RespValue>, id: impl Into<RespValue>, count: i64) -> Command {
+        Command(resp_array!["xread", "count", count.to_string(), "streams", key, id])
+    }
+
+    /// 按模式遍历键
+    pub fn scan(cursor: impl Into<RespValue>, pattern: impl Into<RespValue>, count: i64) -> Command {
+        Command(resp_array!["scan", cursor, "match", pattern, "count", count.to_string()])
+    }
+
+    /// 数组push多个值
+    pub fn lpush_all(key: impl Into<RespValue>, values: Vec<String>) -> Command {
+        Command(resp_array!["lpush", key].append(values))
+    }
+
+    /// 数组尾部push多个值
+    pub fn rpush_all(key: impl Into<RespValue>, values: Vec<String>) -> Command {
+        Command(resp_array!["rpush", key].append(values))
     }
 }
 
@@ -0,0 +1,146 @@
+/// 文本中以某个符号开头的片段，比如 @nick
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEntity {
+    /// 不包括开头的符号
+    pub text: String,
+    /// 开始位置（按 UTF-16 计算，和 js 的字符串下标一致）
+    pub start: i32,
+    /// 长度（按 UTF-16 计算，包括开头的符号）
+    pub length: i32,
+}
+
+impl TextEntity {
+    /// text 的所有前缀，从长到短
+    pub fn prefixes(&self) -> Vec<String> {
+        let chars: Vec<char> = self.text.chars().collect();
+        (1..=chars.len()).rev().map(|len| chars[..len].iter().collect()).collect()
+    }
+
+    /// 只保留 text 的前 len 个字符
+    pub fn truncated(&self, len: usize) -> Self {
+        let removed: usize = self.text.chars().skip(len).map(char::len_utf16).sum();
+        Self {
+            text: self.text.chars().take(len).collect(),
+            start: self.start,
+            length: self.length - removed as i32,
+        }
+    }
+}
+
+/// 提取以 prefix 开头的片段
+/// prefix 前面是英文字母数字时不算（比如邮箱），超过 max_len 的也不算
+pub fn extract(content: &str, prefix: char, max_len: usize) -> Vec<TextEntity> {
+    scan(content, prefix, max_len, false)
+}
+
+/// 同 extract，但超过 max_len 的截断到 max_len
+/// 中文的 @昵称 后面经常不加空格，需要截断后再按前缀匹配
+pub fn extract_truncated(content: &str, prefix: char, max_len: usize) -> Vec<TextEntity> {
+    scan(content, prefix, max_len, true)
+}
+
+fn scan(content: &str, prefix: char, max_len: usize, truncate: bool) -> Vec<TextEntity> {
+    let chars: Vec<char> = content.chars().collect();
+    // 每个字符的 UTF-16 开始位置
+    let mut offsets = Vec::with_capacity(chars.len() + 1);
+    let mut offset = 0;
+    for c in chars.iter() {
+        offsets.push(offset);
+        offset += c.len_utf16();
+    }
+    offsets.push(offset);
+
+    let mut list = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let is_start = chars[i] == prefix && (i == 0 || !is_word_char(chars[i - 1]));
+        if !is_start {
+            i += 1;
+            continue;
+        }
+        let end = chars[i + 1..]
+            .iter()
+            .position(|c| !is_entity_char(*c))
+            .map_or(chars.len(), |len| i + 1 + len);
+        let len = end - i - 1;
+        let text_end = if truncate { i + 1 + len.min(max_len) } else { end };
+        if len > 0 && (truncate || len <= max_len) {
+            list.push(TextEntity {
+                text: chars[i + 1..text_end].iter().collect(),
+                start: offsets[i] as i32,
+                length: (offsets[text_end] - offsets[i]) as i32,
+            });
+        }
+        i = end;
+    }
+    list
+}
+
+fn is_entity_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+/// 中文后面直接跟 @ 是常见写法，只有英文单词后面的不算
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity(text: &str, start: i32, length: i32) -> TextEntity {
+        TextEntity { text: text.to_string(), start, length }
+    }
+
+    #[test]
+    fn extract_positions() {
+        assert_eq!(
+            extract("hi @tom and @jerry_1!", '@', 15),
+            vec![entity("tom", 3, 4), entity("jerry_1", 12, 8)]
+        );
+        assert_eq!(extract("#rust", '#', 32), vec![entity("rust", 0, 5)]);
+    }
+
+    #[test]
+    fn extract_skips_email() {
+        assert_eq!(extract("mail me: tom@example.com", '@', 15), vec![]);
+        assert_eq!(extract("a@b @c", '@', 15), vec![entity("c", 4, 2)]);
+    }
+
+    #[test]
+    fn extract_skips_empty() {
+        assert_eq!(extract("@ @", '@', 15), vec![]);
+        assert_eq!(extract("@@tom", '@', 15), vec![entity("tom", 1, 4)]);
+    }
+
+    #[test]
+    fn extract_max_len() {
+        assert_eq!(extract("@abcde", '@', 5), vec![entity("abcde", 0, 6)]);
+        assert_eq!(extract("@abcdef", '@', 5), vec![]);
+        assert_eq!(extract_truncated("@abcdef", '@', 5), vec![entity("abcde", 0, 6)]);
+    }
+
+    #[test]
+    fn extract_cjk() {
+        // 中文后面不加空格时整段都算进去
+        assert_eq!(extract("你好@张三你好", '@', 15), vec![entity("张三你好", 2, 5)]);
+        assert_eq!(extract("@张三，你好", '@', 15), vec![entity("张三", 0, 3)]);
+    }
+
+    #[test]
+    fn extract_utf16_offsets() {
+        // emoji 在 UTF-16 里占两个位置
+        assert_eq!(extract("😀 @tom", '@', 15), vec![entity("tom", 3, 4)]);
+        assert_eq!(extract("@a😀", '@', 15), vec![entity("a", 0, 2)]);
+        assert_eq!(extract_truncated("😀@𝒜𝒜𝒜", '@', 2), vec![entity("𝒜𝒜", 2, 5)]);
+    }
+
+    #[test]
+    fn entity_prefixes() {
+        let e = entity("张三你", 0, 4);
+        assert_eq!(e.prefixes(), vec!["张三你", "张三", "张"]);
+        assert_eq!(e.truncated(2), entity("张三", 0, 3));
+        assert_eq!(entity("a𝒜b", 1, 5).truncated(2), entity("a𝒜", 1, 4));
+    }
+}
@@ -1,5 +1,6 @@
 pub mod extension;
 pub mod db_helper;
+pub mod entity;
 
 use rand::{thread_rng, Rng, distributions::Alphanumeric};
 
@@ -7,6 +8,22 @@ pub fn get_random_string(len: usize) -> String {
     String::from_utf8(thread_rng().sample_iter(&Alphanumeric).take(len).collect()).unwrap()
 }
 
+/// 转义 html 特殊字符
+pub fn escape_html(text: &str) -> String {
+    let mut html = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#x27;"),
+            _ => html.push(c),
+        }
+    }
+    html
+}
+
 // pub fn string_to_i64(str: &String) -> i64 {
 //     str.parse().unwrap_or(0)
 // }
\ No newline at end of file